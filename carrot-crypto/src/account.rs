@@ -1,8 +1,14 @@
 use zeroize::ZeroizeOnDrop;
 
+use crate::destination::*;
+use crate::device::GenerateAddressSecretDevice;
 use crate::domain_separators;
+use crate::enote::CarrotOwnedEnoteRecordV1;
+use crate::enote_components::{KeyImage, OnetimeExtensionG, OnetimeExtensionT, OutputPubkey};
 use crate::hash_functions::*;
 use crate::math_utils::*;
+use crate::opening::*;
+use crate::scan::make_carrot_key_image;
 use crate::transcript::*;
 use crate::type_macros::*;
 
@@ -185,11 +191,617 @@ impl SubaddressScalarSecret {
             s_address_index_preimage_2.as_bytes(),
         ))
     }
+
+    /// Derive subaddress scalar secret for an address index, which is 1 for the main address
+    pub fn derive_from_index<G: GenerateAddressSecretDevice>(
+        s_generate_address_dev: &G,
+        account_spend_pubkey: &AddressSpendPubkey,
+        account_view_pubkey: &AddressViewPubkey,
+        major_index: u32,
+        minor_index: u32,
+    ) -> Option<Self> {
+        // k^0_subscal = 1
+        if major_index == 0 && minor_index == 0 {
+            return Some(Self::from(1u64));
+        }
+
+        // s^j_ap1 = H_32[s_ga](j_major, j_minor)
+        let s_address_index_preimage_1 = s_generate_address_dev
+            .make_address_index_preimage_1(major_index, minor_index)
+            .ok()?;
+
+        // s^j_ap2 = H_32[s^j_ap1](j_major, j_minor, K_s, K_v)
+        let s_address_index_preimage_2 = AddressIndexPreimage2::derive(
+            &s_address_index_preimage_1,
+            major_index,
+            minor_index,
+            account_spend_pubkey,
+            account_view_pubkey,
+        );
+
+        // k^j_subscal = H_n[s^j_ap2](K_s)
+        Some(Self::derive(
+            &s_address_index_preimage_2,
+            account_spend_pubkey,
+        ))
+    }
+}
+
+/// Carrot-derived account holding the full key hierarchy, derived from a master secret
+pub struct CarrotAccount {
+    s_master: MasterSecret,
+    k_prove_spend: ProveSpendKey,
+    s_view_balance: ViewBalanceSecret,
+    k_generate_image: GenerateImageKey,
+    k_view_incoming: ViewIncomingKey,
+    s_generate_address: GenerateAddressSecret,
+    account_spend_pubkey: AddressSpendPubkey,
+    account_view_pubkey: AddressViewPubkey,
+    primary_address_view_pubkey: AddressViewPubkey,
+}
+
+impl CarrotAccount {
+    /// Derive the entire Carrot key hierarchy from the master secret
+    pub fn from_master_secret(s_master: MasterSecret) -> Self {
+        // k_ps = H_n(s_m)
+        let k_prove_spend = ProveSpendKey::derive(&s_master);
+
+        // K_ps = k_ps T
+        let partial_account_spend_pubkey = PartialAccountSpendPubkey::derive(&k_prove_spend);
+
+        // s_vb = H_32(s_m)
+        let s_view_balance = ViewBalanceSecret::derive(&s_master);
+
+        // s_gp = H_32(s_vb)
+        let s_generate_image_preimage = GenerateImagePreimage::derive(&s_view_balance);
+
+        // k_gi = H_n(s_gp, K_ps)
+        let k_generate_image =
+            GenerateImageKey::derive(&s_generate_image_preimage, &partial_account_spend_pubkey);
+
+        // k_v = H_n(s_vb)
+        let k_view_incoming = ViewIncomingKey::derive(&s_view_balance);
+
+        // s_ga = H_32(s_vb)
+        let s_generate_address = GenerateAddressSecret::derive(&s_view_balance);
+
+        // K_s = k_gi G + k_ps T
+        let account_spend_pubkey = AddressSpendPubkey::derive_carrot_account_spend_pubkey(
+            &k_generate_image,
+            &k_prove_spend,
+        );
+
+        // K_v = k_v K_s
+        let account_view_pubkey = AddressViewPubkey::derive_carrot_account_view_pubkey(
+            &k_view_incoming,
+            &account_spend_pubkey,
+        )
+        .expect("account spend pubkey is always a valid point");
+
+        // K^0_v = k_v G
+        let primary_address_view_pubkey =
+            AddressViewPubkey::derive_primary_address_view_pubkey(&k_view_incoming);
+
+        Self {
+            s_master,
+            k_prove_spend,
+            s_view_balance,
+            k_generate_image,
+            k_view_incoming,
+            s_generate_address,
+            account_spend_pubkey,
+            account_view_pubkey,
+            primary_address_view_pubkey,
+        }
+    }
+
+    /// Master secret s_m
+    pub fn master_secret(&self) -> &MasterSecret {
+        &self.s_master
+    }
+
+    /// Prove-spend key k_ps
+    pub fn prove_spend_key(&self) -> &ProveSpendKey {
+        &self.k_prove_spend
+    }
+
+    /// View-balance secret s_vb
+    pub fn view_balance_secret(&self) -> &ViewBalanceSecret {
+        &self.s_view_balance
+    }
+
+    /// Generate-image key k_gi
+    pub fn generate_image_key(&self) -> &GenerateImageKey {
+        &self.k_generate_image
+    }
+
+    /// View-incoming key k_v
+    pub fn view_incoming_key(&self) -> &ViewIncomingKey {
+        &self.k_view_incoming
+    }
+
+    /// Generate-address secret s_ga
+    pub fn generate_address_secret(&self) -> &GenerateAddressSecret {
+        &self.s_generate_address
+    }
+
+    /// Account spend pubkey K_s
+    pub fn account_spend_pubkey(&self) -> &AddressSpendPubkey {
+        &self.account_spend_pubkey
+    }
+
+    /// Account view pubkey K_v
+    pub fn account_view_pubkey(&self) -> &AddressViewPubkey {
+        &self.account_view_pubkey
+    }
+
+    /// Primary address view pubkey K^0_v
+    pub fn primary_address_view_pubkey(&self) -> &AddressViewPubkey {
+        &self.primary_address_view_pubkey
+    }
+
+    /// Main address (K_s, K^0_v)
+    pub fn main_address(&self) -> CarrotDestinationV1 {
+        CarrotDestinationV1::make_main_address(
+            self.account_spend_pubkey.clone(),
+            self.primary_address_view_pubkey.clone(),
+        )
+    }
+
+    /// Subaddress at index (j_major, j_minor), or the main address at index (0, 0)
+    pub fn subaddress(&self, major_index: u32, minor_index: u32) -> Option<CarrotDestinationV1> {
+        make_account_subaddress(
+            &self.account_spend_pubkey,
+            &self.account_view_pubkey,
+            &self.primary_address_view_pubkey,
+            &self.s_generate_address,
+            major_index,
+            minor_index,
+        )
+    }
+
+    /// Integrated address (K_s, K^0_v, pid)
+    pub fn integrated_address(&self, payment_id: PaymentId) -> CarrotDestinationV1 {
+        CarrotDestinationV1::make_integrated_address(
+            self.account_spend_pubkey.clone(),
+            self.primary_address_view_pubkey.clone(),
+            payment_id,
+        )
+    }
+
+    /// Subaddress scalar k^j_subscal at index (j_major, j_minor), which is 1 at index (0, 0)
+    pub fn subaddress_scalar(
+        &self,
+        major_index: u32,
+        minor_index: u32,
+    ) -> Option<SubaddressScalarSecret> {
+        SubaddressScalarSecret::derive_from_index(
+            &self.s_generate_address,
+            &self.account_spend_pubkey,
+            &self.account_view_pubkey,
+            major_index,
+            minor_index,
+        )
+    }
+
+    /// Opening scalars (k^g_a, k^t_a) of the address spend pubkey at index (j_major, j_minor)
+    pub fn opening_for_subaddress(
+        &self,
+        major_index: u32,
+        minor_index: u32,
+    ) -> Option<(OpeningScalarSecret, OpeningScalarSecret)> {
+        // k^j_subscal = H_n[s^j_ap2](K_s)
+        let subaddr_scalar = self.subaddress_scalar(major_index, minor_index)?;
+
+        // k^g_a = k_gi * k^j_subscal, k^t_a = k_ps * k^j_subscal
+        Some(make_carrot_address_opening(
+            &self.k_prove_spend,
+            &self.k_generate_image,
+            &subaddr_scalar,
+        ))
+    }
+
+    /// Opening (x, y) of a one-time address K_o = x G + y T received to index (j_major, j_minor),
+    /// or None if the scanned enote wasn't actually addressed to that index
+    pub fn try_open_onetime_address(
+        &self,
+        major_index: u32,
+        minor_index: u32,
+        sender_extension_g: &OnetimeExtensionG,
+        sender_extension_t: &OnetimeExtensionT,
+        onetime_address: &OutputPubkey,
+    ) -> Option<(OpeningScalarSecret, OpeningScalarSecret)> {
+        let (address_privkey_g, address_privkey_t) =
+            self.opening_for_subaddress(major_index, minor_index)?;
+
+        // x = k^g_a + k^g_o, y = k^t_a + k^t_o
+        try_open_onetime_address(
+            &address_privkey_g,
+            &address_privkey_t,
+            sender_extension_g,
+            sender_extension_t,
+            onetime_address,
+        )
+    }
+
+    /// Key image L of a one-time address K_o received to index (j_major, j_minor)
+    pub fn key_image(
+        &self,
+        major_index: u32,
+        minor_index: u32,
+        sender_extension_g: &OnetimeExtensionG,
+        onetime_address: &OutputPubkey,
+    ) -> Option<KeyImage> {
+        // k^j_subscal = H_n[s^j_ap2](K_s)
+        let subaddr_scalar = self.subaddress_scalar(major_index, minor_index)?;
+
+        // L = (k^j_subscal k_gi + k^g_o) Hp(Ko)
+        make_carrot_key_image(
+            onetime_address,
+            sender_extension_g,
+            &subaddr_scalar,
+            &self.k_generate_image,
+        )
+        .ok()
+    }
+
+    /// Opening (x, y) of the one-time address of an owned enote record, or None if the record wasn't
+    /// received to a Carrot-derived address of this account
+    pub fn try_open_enote_record(
+        &self,
+        record: &CarrotOwnedEnoteRecordV1,
+    ) -> Option<(OpeningScalarSecret, OpeningScalarSecret)> {
+        let subaddr_index = record.subaddress_index();
+        if subaddr_index.derive_type != AddressDeriveType::Carrot {
+            return None;
+        }
+        self.try_open_onetime_address(
+            subaddr_index.index.major,
+            subaddr_index.index.minor,
+            record.sender_extension_g(),
+            record.sender_extension_t(),
+            record.onetime_address(),
+        )
+    }
+
+    /// Key image L of the one-time address of an owned enote record, or None if the record wasn't
+    /// received to a Carrot-derived address of this account
+    pub fn enote_record_key_image(&self, record: &CarrotOwnedEnoteRecordV1) -> Option<KeyImage> {
+        let subaddr_index = record.subaddress_index();
+        if subaddr_index.derive_type != AddressDeriveType::Carrot {
+            return None;
+        }
+        self.key_image(
+            subaddr_index.index.major,
+            subaddr_index.index.minor,
+            record.sender_extension_g(),
+            record.onetime_address(),
+        )
+    }
+
+    /// View-only account tier which can scan all enotes, but cannot spend them
+    pub fn view_balance_account(&self) -> ViewBalanceAccount {
+        ViewBalanceAccount {
+            s_view_balance: self.s_view_balance.clone(),
+            k_view_incoming: self.k_view_incoming.clone(),
+            s_generate_address: self.s_generate_address.clone(),
+            account_spend_pubkey: self.account_spend_pubkey.clone(),
+            account_view_pubkey: self.account_view_pubkey.clone(),
+            primary_address_view_pubkey: self.primary_address_view_pubkey.clone(),
+        }
+    }
+}
+
+impl Random for CarrotAccount {
+    type Params = ();
+    fn new_random_with_params<R: rand_core::CryptoRngCore + ?Sized>(
+        rng: &mut R,
+        _: Self::Params,
+    ) -> Self {
+        Self::from_master_secret(MasterSecret::new_random_with_params(rng, ()))
+    }
+}
+
+/// View-only Carrot account tier holding the view-balance secret, which can scan all incoming and
+/// internal enotes and generate addresses, but cannot spend nor compute key images
+pub struct ViewBalanceAccount {
+    s_view_balance: ViewBalanceSecret,
+    k_view_incoming: ViewIncomingKey,
+    s_generate_address: GenerateAddressSecret,
+    account_spend_pubkey: AddressSpendPubkey,
+    account_view_pubkey: AddressViewPubkey,
+    primary_address_view_pubkey: AddressViewPubkey,
+}
+
+impl ViewBalanceAccount {
+    /// Derive the view-only key hierarchy from the view-balance secret and account spend pubkey,
+    /// failing if the account spend pubkey is not a valid point
+    pub fn new(
+        s_view_balance: ViewBalanceSecret,
+        account_spend_pubkey: AddressSpendPubkey,
+    ) -> Option<Self> {
+        // k_v = H_n(s_vb)
+        let k_view_incoming = ViewIncomingKey::derive(&s_view_balance);
+
+        // s_ga = H_32(s_vb)
+        let s_generate_address = GenerateAddressSecret::derive(&s_view_balance);
+
+        // K_v = k_v K_s
+        let account_view_pubkey = AddressViewPubkey::derive_carrot_account_view_pubkey(
+            &k_view_incoming,
+            &account_spend_pubkey,
+        )?;
+
+        // K^0_v = k_v G
+        let primary_address_view_pubkey =
+            AddressViewPubkey::derive_primary_address_view_pubkey(&k_view_incoming);
+
+        Some(Self {
+            s_view_balance,
+            k_view_incoming,
+            s_generate_address,
+            account_spend_pubkey,
+            account_view_pubkey,
+            primary_address_view_pubkey,
+        })
+    }
+
+    /// View-balance secret s_vb
+    pub fn view_balance_secret(&self) -> &ViewBalanceSecret {
+        &self.s_view_balance
+    }
+
+    /// View-incoming key k_v
+    pub fn view_incoming_key(&self) -> &ViewIncomingKey {
+        &self.k_view_incoming
+    }
+
+    /// Generate-address secret s_ga
+    pub fn generate_address_secret(&self) -> &GenerateAddressSecret {
+        &self.s_generate_address
+    }
+
+    /// Account spend pubkey K_s
+    pub fn account_spend_pubkey(&self) -> &AddressSpendPubkey {
+        &self.account_spend_pubkey
+    }
+
+    /// Account view pubkey K_v
+    pub fn account_view_pubkey(&self) -> &AddressViewPubkey {
+        &self.account_view_pubkey
+    }
+
+    /// Primary address view pubkey K^0_v
+    pub fn primary_address_view_pubkey(&self) -> &AddressViewPubkey {
+        &self.primary_address_view_pubkey
+    }
+
+    /// Main address (K_s, K^0_v)
+    pub fn main_address(&self) -> CarrotDestinationV1 {
+        CarrotDestinationV1::make_main_address(
+            self.account_spend_pubkey.clone(),
+            self.primary_address_view_pubkey.clone(),
+        )
+    }
+
+    /// Subaddress at index (j_major, j_minor), or the main address at index (0, 0)
+    pub fn subaddress(&self, major_index: u32, minor_index: u32) -> Option<CarrotDestinationV1> {
+        make_account_subaddress(
+            &self.account_spend_pubkey,
+            &self.account_view_pubkey,
+            &self.primary_address_view_pubkey,
+            &self.s_generate_address,
+            major_index,
+            minor_index,
+        )
+    }
+
+    /// Integrated address (K_s, K^0_v, pid)
+    pub fn integrated_address(&self, payment_id: PaymentId) -> CarrotDestinationV1 {
+        CarrotDestinationV1::make_integrated_address(
+            self.account_spend_pubkey.clone(),
+            self.primary_address_view_pubkey.clone(),
+            payment_id,
+        )
+    }
+
+    /// View-only account tier which can scan external and special enotes, but not internal enotes
+    pub fn view_incoming_account(&self) -> ViewIncomingAccount {
+        ViewIncomingAccount {
+            k_view_incoming: self.k_view_incoming.clone(),
+            account_spend_pubkey: self.account_spend_pubkey.clone(),
+            account_view_pubkey: self.account_view_pubkey.clone(),
+            primary_address_view_pubkey: self.primary_address_view_pubkey.clone(),
+        }
+    }
+
+    /// Account tier which can only generate addresses
+    pub fn address_generator_account(&self) -> AddressGeneratorAccount {
+        AddressGeneratorAccount {
+            s_generate_address: self.s_generate_address.clone(),
+            account_spend_pubkey: self.account_spend_pubkey.clone(),
+            account_view_pubkey: self.account_view_pubkey.clone(),
+            primary_address_view_pubkey: self.primary_address_view_pubkey.clone(),
+        }
+    }
+}
+
+/// View-only Carrot account tier holding the view-incoming key, which can scan external and special
+/// enotes, but cannot scan internal enotes nor generate subaddresses
+pub struct ViewIncomingAccount {
+    k_view_incoming: ViewIncomingKey,
+    account_spend_pubkey: AddressSpendPubkey,
+    account_view_pubkey: AddressViewPubkey,
+    primary_address_view_pubkey: AddressViewPubkey,
+}
+
+impl ViewIncomingAccount {
+    /// Derive the account pubkeys from the view-incoming key and account spend pubkey, failing if
+    /// the account spend pubkey is not a valid point
+    pub fn new(
+        k_view_incoming: ViewIncomingKey,
+        account_spend_pubkey: AddressSpendPubkey,
+    ) -> Option<Self> {
+        // K_v = k_v K_s
+        let account_view_pubkey = AddressViewPubkey::derive_carrot_account_view_pubkey(
+            &k_view_incoming,
+            &account_spend_pubkey,
+        )?;
+
+        // K^0_v = k_v G
+        let primary_address_view_pubkey =
+            AddressViewPubkey::derive_primary_address_view_pubkey(&k_view_incoming);
+
+        Some(Self {
+            k_view_incoming,
+            account_spend_pubkey,
+            account_view_pubkey,
+            primary_address_view_pubkey,
+        })
+    }
+
+    /// View-incoming key k_v
+    pub fn view_incoming_key(&self) -> &ViewIncomingKey {
+        &self.k_view_incoming
+    }
+
+    /// Account spend pubkey K_s
+    pub fn account_spend_pubkey(&self) -> &AddressSpendPubkey {
+        &self.account_spend_pubkey
+    }
+
+    /// Account view pubkey K_v
+    pub fn account_view_pubkey(&self) -> &AddressViewPubkey {
+        &self.account_view_pubkey
+    }
+
+    /// Primary address view pubkey K^0_v
+    pub fn primary_address_view_pubkey(&self) -> &AddressViewPubkey {
+        &self.primary_address_view_pubkey
+    }
+
+    /// Main address (K_s, K^0_v)
+    pub fn main_address(&self) -> CarrotDestinationV1 {
+        CarrotDestinationV1::make_main_address(
+            self.account_spend_pubkey.clone(),
+            self.primary_address_view_pubkey.clone(),
+        )
+    }
+
+    /// Integrated address (K_s, K^0_v, pid)
+    pub fn integrated_address(&self, payment_id: PaymentId) -> CarrotDestinationV1 {
+        CarrotDestinationV1::make_integrated_address(
+            self.account_spend_pubkey.clone(),
+            self.primary_address_view_pubkey.clone(),
+            payment_id,
+        )
+    }
+}
+
+/// Carrot account tier holding the generate-address secret, which can generate subaddresses, but
+/// cannot scan any enotes
+pub struct AddressGeneratorAccount {
+    s_generate_address: GenerateAddressSecret,
+    account_spend_pubkey: AddressSpendPubkey,
+    account_view_pubkey: AddressViewPubkey,
+    primary_address_view_pubkey: AddressViewPubkey,
+}
+
+impl AddressGeneratorAccount {
+    /// Bundle the generate-address secret with the account pubkeys
+    pub fn new(
+        s_generate_address: GenerateAddressSecret,
+        account_spend_pubkey: AddressSpendPubkey,
+        account_view_pubkey: AddressViewPubkey,
+        primary_address_view_pubkey: AddressViewPubkey,
+    ) -> Self {
+        Self {
+            s_generate_address,
+            account_spend_pubkey,
+            account_view_pubkey,
+            primary_address_view_pubkey,
+        }
+    }
+
+    /// Generate-address secret s_ga
+    pub fn generate_address_secret(&self) -> &GenerateAddressSecret {
+        &self.s_generate_address
+    }
+
+    /// Account spend pubkey K_s
+    pub fn account_spend_pubkey(&self) -> &AddressSpendPubkey {
+        &self.account_spend_pubkey
+    }
+
+    /// Account view pubkey K_v
+    pub fn account_view_pubkey(&self) -> &AddressViewPubkey {
+        &self.account_view_pubkey
+    }
+
+    /// Primary address view pubkey K^0_v
+    pub fn primary_address_view_pubkey(&self) -> &AddressViewPubkey {
+        &self.primary_address_view_pubkey
+    }
+
+    /// Main address (K_s, K^0_v)
+    pub fn main_address(&self) -> CarrotDestinationV1 {
+        CarrotDestinationV1::make_main_address(
+            self.account_spend_pubkey.clone(),
+            self.primary_address_view_pubkey.clone(),
+        )
+    }
+
+    /// Subaddress at index (j_major, j_minor), or the main address at index (0, 0)
+    pub fn subaddress(&self, major_index: u32, minor_index: u32) -> Option<CarrotDestinationV1> {
+        make_account_subaddress(
+            &self.account_spend_pubkey,
+            &self.account_view_pubkey,
+            &self.primary_address_view_pubkey,
+            &self.s_generate_address,
+            major_index,
+            minor_index,
+        )
+    }
+
+    /// Integrated address (K_s, K^0_v, pid)
+    pub fn integrated_address(&self, payment_id: PaymentId) -> CarrotDestinationV1 {
+        CarrotDestinationV1::make_integrated_address(
+            self.account_spend_pubkey.clone(),
+            self.primary_address_view_pubkey.clone(),
+            payment_id,
+        )
+    }
+}
+
+fn make_account_subaddress(
+    account_spend_pubkey: &AddressSpendPubkey,
+    account_view_pubkey: &AddressViewPubkey,
+    primary_address_view_pubkey: &AddressViewPubkey,
+    s_generate_address: &GenerateAddressSecret,
+    major_index: u32,
+    minor_index: u32,
+) -> Option<CarrotDestinationV1> {
+    // the (0, 0) index is the main address, not a subaddress
+    if major_index == 0 && minor_index == 0 {
+        return Some(CarrotDestinationV1::make_main_address(
+            account_spend_pubkey.clone(),
+            primary_address_view_pubkey.clone(),
+        ));
+    }
+
+    CarrotDestinationV1::make_subaddress(
+        account_spend_pubkey,
+        account_view_pubkey,
+        s_generate_address,
+        major_index,
+        minor_index,
+    )
 }
 
 #[cfg(test)]
 mod test {
     use crate::account::*;
+    use crate::enote_components::OnetimeExtension;
     use crate::unit_testing::*;
 
     #[test]
@@ -310,4 +922,95 @@ mod test {
             )
         );
     }
+
+    #[test]
+    fn converge_carrot_account() {
+        let account = CarrotAccount::from_master_secret(hex_into!(
+            "6e02e67b303dc713276bb1a4d70b0083b78e4f50e34e209da9f0377cdc3d376e"
+        ));
+        assert_eq_hex!(
+            "4198f391723f6c64eb75e4f0e341d576dc344e8a8ad3164444451855dbd862b4",
+            account.account_spend_pubkey()
+        );
+        assert_eq_hex!(
+            "14d12188409591353096b41abeccf66a88d916dfe0e6d1998672293ebc1cc83d",
+            account.account_view_pubkey()
+        );
+        assert_eq_hex!(
+            "8f2f38e702678ae59751dc55818240e0330851e77bfaff003b671885ed06871e",
+            account.subaddress(5, 16).unwrap().address_spend_pubkey
+        );
+        assert_eq!(account.main_address(), account.subaddress(0, 0).unwrap());
+    }
+
+    #[test]
+    fn carrot_account_open_onetime_address() {
+        let account = CarrotAccount::new_random_with_params(&mut rand_core::OsRng, ());
+        let sender_extension_g =
+            OnetimeExtensionG::new_random_with_params(&mut rand_core::OsRng, ());
+        let sender_extension_t =
+            OnetimeExtensionT::new_random_with_params(&mut rand_core::OsRng, ());
+        for (major_index, minor_index) in [(0, 0), (0, 1), (3, 2)] {
+            // K_o = K^j_s + k^g_o G + k^t_o T
+            let onetime_address = OutputPubkey::derive_from_extension(
+                &account
+                    .subaddress(major_index, minor_index)
+                    .unwrap()
+                    .address_spend_pubkey,
+                &OnetimeExtension::derive_from_scalars(&sender_extension_g, &sender_extension_t),
+            )
+            .unwrap();
+
+            assert!(
+                account
+                    .try_open_onetime_address(
+                        major_index,
+                        minor_index,
+                        &sender_extension_g,
+                        &sender_extension_t,
+                        &onetime_address
+                    )
+                    .is_some()
+            );
+            assert!(
+                account
+                    .try_open_onetime_address(
+                        major_index + 1,
+                        minor_index,
+                        &sender_extension_g,
+                        &sender_extension_t,
+                        &onetime_address
+                    )
+                    .is_none()
+            );
+        }
+    }
+
+    #[test]
+    fn converge_carrot_account_tiers() {
+        let view_balance_account = ViewBalanceAccount::new(
+            hex_into!("59b2ee8646923309384704613418f5982b0167eb3cd87c6c067ee10700c3af91"),
+            hex_into!("4198f391723f6c64eb75e4f0e341d576dc344e8a8ad3164444451855dbd862b4"),
+        )
+        .unwrap();
+        assert_eq_hex!(
+            "14d12188409591353096b41abeccf66a88d916dfe0e6d1998672293ebc1cc83d",
+            view_balance_account.account_view_pubkey()
+        );
+
+        let view_incoming_account = view_balance_account.view_incoming_account();
+        assert_eq!(
+            view_balance_account.main_address(),
+            view_incoming_account.main_address()
+        );
+
+        let address_generator_account = view_balance_account.address_generator_account();
+        assert_eq_hex!(
+            "8f2f38e702678ae59751dc55818240e0330851e77bfaff003b671885ed06871e",
+            address_generator_account
+                .subaddress(5, 16)
+                .unwrap()
+                .address_spend_pubkey
+        );
+    }
 }
@@ -0,0 +1,359 @@
+use core::fmt::{Debug, Display};
+
+use crate::as_crypto::AsEdwardsPoint;
+use crate::base58;
+use crate::consts::*;
+use crate::destination::*;
+use crate::hash_functions::keccak256;
+use crate::math_utils::is_invalid_or_has_torsion;
+use crate::*;
+
+const ADDRESS_CHECKSUM_BYTES: usize = 4;
+const STANDARD_ADDRESS_BYTES: usize = 1 + 32 + 32 + ADDRESS_CHECKSUM_BYTES;
+const INTEGRATED_ADDRESS_BYTES: usize = STANDARD_ADDRESS_BYTES + PAYMENT_ID_BYTES;
+const MAX_ADDRESS_CHARS: usize = base58::encoded_len(INTEGRATED_ADDRESS_BYTES);
+
+/// Monero network that an address is valid for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Stagenet,
+}
+
+/// Kind of Monero address, which together with the network determines the address prefix
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AddressKind {
+    Standard,
+    Integrated,
+    Subaddress,
+}
+
+/// Type of error encountered encoding or decoding addresses
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Address contains invalid/torsioned elliptic curve points
+    BadAddressPoints,
+    /// Address checksum does not match address contents
+    BadChecksum,
+    /// String is not valid Monero Base58
+    BadEncoding,
+    /// Decoded address has the wrong number of bytes for its prefix
+    BadLength,
+    /// Address prefix does not belong to any known network and address kind
+    UnknownPrefix,
+    /// Destination can't be represented as an address, e.g. an integrated subaddress
+    WrongAddressType,
+}
+
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+}
+
+/// @TODO: real display
+impl Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(&self.kind, f)
+    }
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind) -> Self {
+        Self { kind }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+impl core::error::Error for Error {}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// Base58-encoded Monero address string, stored inline so that encoding does not allocate
+#[derive(Clone)]
+pub struct EncodedAddress {
+    chars: [u8; MAX_ADDRESS_CHARS],
+    len: usize,
+}
+
+impl EncodedAddress {
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.chars[..self.len]).expect("base58 alphabet is ASCII")
+    }
+}
+
+impl AsRef<str> for EncodedAddress {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Display for EncodedAddress {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Debug for EncodedAddress {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl PartialEq for EncodedAddress {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for EncodedAddress {}
+
+fn address_prefix(network: Network, kind: AddressKind) -> u8 {
+    match (network, kind) {
+        (Network::Mainnet, AddressKind::Standard) => 18,
+        (Network::Mainnet, AddressKind::Integrated) => 19,
+        (Network::Mainnet, AddressKind::Subaddress) => 42,
+        (Network::Testnet, AddressKind::Standard) => 53,
+        (Network::Testnet, AddressKind::Integrated) => 54,
+        (Network::Testnet, AddressKind::Subaddress) => 63,
+        (Network::Stagenet, AddressKind::Standard) => 24,
+        (Network::Stagenet, AddressKind::Integrated) => 25,
+        (Network::Stagenet, AddressKind::Subaddress) => 36,
+    }
+}
+
+fn parse_address_prefix(prefix: u8) -> Option<(Network, AddressKind)> {
+    // all known prefixes are < 0x80, so their varint encoding is a single byte
+    [Network::Mainnet, Network::Testnet, Network::Stagenet]
+        .into_iter()
+        .flat_map(|network| {
+            [
+                AddressKind::Standard,
+                AddressKind::Integrated,
+                AddressKind::Subaddress,
+            ]
+            .into_iter()
+            .map(move |kind| (network, kind))
+        })
+        .find(|&(network, kind)| address_prefix(network, kind) == prefix)
+}
+
+fn address_checksum(data: &[u8]) -> [u8; ADDRESS_CHECKSUM_BYTES] {
+    // checksum = H(prefix || K^j_s || K^j_v || [pid])[:4]
+    let hash = keccak256(data);
+    let mut checksum = [0u8; ADDRESS_CHECKSUM_BYTES];
+    checksum.copy_from_slice(&hash[..ADDRESS_CHECKSUM_BYTES]);
+    checksum
+}
+
+impl CarrotDestinationV1 {
+    /// Encode this destination as a Base58 Monero address string for the given network
+    pub fn to_address_string(&self, network: Network) -> Result<EncodedAddress> {
+        // 1. sanity checks
+        if is_invalid_or_has_torsion(&self.address_spend_pubkey)
+            || is_invalid_or_has_torsion(&self.address_view_pubkey)
+        {
+            return Err(Error::new(ErrorKind::BadAddressPoints));
+        }
+        let kind = match (self.is_subaddress, self.is_integrated()) {
+            (false, false) => AddressKind::Standard,
+            (false, true) => AddressKind::Integrated,
+            (true, false) => AddressKind::Subaddress,
+            (true, true) => return Err(Error::new(ErrorKind::WrongAddressType)),
+        };
+
+        // 2. data = prefix || K^j_s || K^j_v || [pid]
+        let mut data = [0u8; INTEGRATED_ADDRESS_BYTES];
+        data[0] = address_prefix(network, kind);
+        data[1..33].copy_from_slice(&self.address_spend_pubkey.as_edwards_ref().0);
+        data[33..65].copy_from_slice(&self.address_view_pubkey.as_edwards_ref().0);
+        let mut data_len = 65;
+        if kind == AddressKind::Integrated {
+            data[data_len..(data_len + PAYMENT_ID_BYTES)]
+                .copy_from_slice(self.payment_id.as_bytes());
+            data_len += PAYMENT_ID_BYTES;
+        }
+
+        // 3. data = data || checksum
+        let checksum = address_checksum(&data[..data_len]);
+        data[data_len..(data_len + ADDRESS_CHECKSUM_BYTES)].copy_from_slice(&checksum);
+        data_len += ADDRESS_CHECKSUM_BYTES;
+
+        // 4. Base58 encode
+        let mut encoded = EncodedAddress {
+            chars: [0u8; MAX_ADDRESS_CHARS],
+            len: base58::encoded_len(data_len),
+        };
+        base58::encode(&data[..data_len], &mut encoded.chars[..encoded.len]);
+        Ok(encoded)
+    }
+
+    /// Decode a Base58 Monero address string into a destination and the network it belongs to
+    pub fn from_address_string(address: &str) -> Result<(Self, Network)> {
+        // 1. Base58 decode
+        let mut data = [0u8; INTEGRATED_ADDRESS_BYTES];
+        let data_len = base58::decode(address.as_bytes(), &mut data)
+            .ok_or(Error::new(ErrorKind::BadEncoding))?;
+        if data_len == 0 {
+            return Err(Error::new(ErrorKind::BadLength));
+        }
+
+        // 2. parse prefix and check length
+        let (network, kind) =
+            parse_address_prefix(data[0]).ok_or(Error::new(ErrorKind::UnknownPrefix))?;
+        let expected_len = match kind {
+            AddressKind::Integrated => INTEGRATED_ADDRESS_BYTES,
+            _ => STANDARD_ADDRESS_BYTES,
+        };
+        if data_len != expected_len {
+            return Err(Error::new(ErrorKind::BadLength));
+        }
+
+        // 3. verify checksum
+        let checksum_idx = data_len - ADDRESS_CHECKSUM_BYTES;
+        if address_checksum(&data[..checksum_idx]) != data[checksum_idx..data_len] {
+            return Err(Error::new(ErrorKind::BadChecksum));
+        }
+
+        // 4. parse K^j_s, K^j_v, and check they lie in the prime order subgroup
+        let mut address_spend_pubkey_bytes = [0u8; 32];
+        address_spend_pubkey_bytes.copy_from_slice(&data[1..33]);
+        let address_spend_pubkey = AddressSpendPubkey::from_bytes(address_spend_pubkey_bytes);
+        let mut address_view_pubkey_bytes = [0u8; 32];
+        address_view_pubkey_bytes.copy_from_slice(&data[33..65]);
+        let address_view_pubkey = AddressViewPubkey::from_bytes(address_view_pubkey_bytes);
+        if is_invalid_or_has_torsion(&address_spend_pubkey)
+            || is_invalid_or_has_torsion(&address_view_pubkey)
+        {
+            return Err(Error::new(ErrorKind::BadAddressPoints));
+        }
+
+        // 5. parse pid, if integrated
+        let payment_id = if kind == AddressKind::Integrated {
+            let mut payment_id_bytes = [0u8; PAYMENT_ID_BYTES];
+            payment_id_bytes.copy_from_slice(&data[65..(65 + PAYMENT_ID_BYTES)]);
+            PaymentId::from(payment_id_bytes)
+        } else {
+            PaymentId::default()
+        };
+
+        Ok((
+            CarrotDestinationV1 {
+                address_spend_pubkey,
+                address_view_pubkey,
+                is_subaddress: kind == AddressKind::Subaddress,
+                payment_id,
+            },
+            network,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use curve25519_dalek::EdwardsPoint;
+    use curve25519_dalek::constants::EIGHT_TORSION;
+
+    use crate::address::*;
+    use crate::random::Random;
+
+    const MONERO_GENERAL_FUND_SUBADDRESS: &str = "888tNkZrPN6JsEgekjMnABU4TBzc2Dt29EPAvkRxbANsAnjyPbb3iQ1YBRk1UXcdRsiKc9dhwMVgN5S9cQUiyoogDavup3H";
+
+    #[test]
+    fn decode_known_subaddress() {
+        let (destination, network) =
+            CarrotDestinationV1::from_address_string(MONERO_GENERAL_FUND_SUBADDRESS).unwrap();
+        assert_eq!(Network::Mainnet, network);
+        assert!(destination.is_subaddress);
+        assert!(!destination.is_integrated());
+        assert_eq!(
+            MONERO_GENERAL_FUND_SUBADDRESS,
+            destination.to_address_string(network).unwrap().as_str()
+        );
+    }
+
+    #[test]
+    fn address_round_trip() {
+        for network in [Network::Mainnet, Network::Testnet, Network::Stagenet] {
+            for (is_subaddress, is_integrated) in [(false, false), (true, false), (false, true)] {
+                let destination = CarrotDestinationV1::new_random_with_params(
+                    &mut rand_core::OsRng,
+                    (is_subaddress, is_integrated),
+                );
+                let address = destination.to_address_string(network).unwrap();
+                let expected_len = if is_integrated { 106 } else { 95 };
+                assert_eq!(expected_len, address.as_str().len());
+
+                let (decoded_destination, decoded_network) =
+                    CarrotDestinationV1::from_address_string(address.as_str()).unwrap();
+                assert_eq!(destination, decoded_destination);
+                assert_eq!(network, decoded_network);
+            }
+        }
+    }
+
+    #[test]
+    fn reject_bad_addresses() {
+        // integrated subaddresses don't exist
+        let destination =
+            CarrotDestinationV1::new_random_with_params(&mut rand_core::OsRng, (true, true));
+        assert_eq!(
+            ErrorKind::WrongAddressType,
+            destination
+                .to_address_string(Network::Mainnet)
+                .unwrap_err()
+                .kind()
+        );
+
+        // flipped character breaks the checksum
+        let mut bad_checksum = [0u8; 95];
+        bad_checksum.copy_from_slice(MONERO_GENERAL_FUND_SUBADDRESS.as_bytes());
+        bad_checksum[50] = if bad_checksum[50] == b'a' { b'b' } else { b'a' };
+        assert_eq!(
+            ErrorKind::BadChecksum,
+            CarrotDestinationV1::from_address_string(core::str::from_utf8(&bad_checksum).unwrap())
+                .unwrap_err()
+                .kind()
+        );
+
+        // truncated address
+        assert_eq!(
+            ErrorKind::BadLength,
+            CarrotDestinationV1::from_address_string(&MONERO_GENERAL_FUND_SUBADDRESS[..88])
+                .unwrap_err()
+                .kind()
+        );
+
+        // torsioned spend pubkey, with an otherwise well-formed encoding
+        let mut destination =
+            CarrotDestinationV1::new_random_with_params(&mut rand_core::OsRng, (false, false));
+        let torsioned_spend_pubkey =
+            EdwardsPoint::new_random_with_params(&mut rand_core::OsRng, ()) + EIGHT_TORSION[1];
+        destination.address_spend_pubkey =
+            AddressSpendPubkey::from_bytes(torsioned_spend_pubkey.compress().0);
+        assert_eq!(
+            ErrorKind::BadAddressPoints,
+            destination
+                .to_address_string(Network::Mainnet)
+                .unwrap_err()
+                .kind()
+        );
+        let mut data = [0u8; STANDARD_ADDRESS_BYTES];
+        data[0] = address_prefix(Network::Mainnet, AddressKind::Standard);
+        data[1..33].copy_from_slice(&destination.address_spend_pubkey.as_edwards_ref().0);
+        data[33..65].copy_from_slice(&destination.address_view_pubkey.as_edwards_ref().0);
+        let checksum = address_checksum(&data[..65]);
+        data[65..].copy_from_slice(&checksum);
+        let mut encoded = [0u8; 95];
+        base58::encode(&data, &mut encoded);
+        assert_eq!(
+            ErrorKind::BadAddressPoints,
+            CarrotDestinationV1::from_address_string(core::str::from_utf8(&encoded).unwrap())
+                .unwrap_err()
+                .kind()
+        );
+    }
+}
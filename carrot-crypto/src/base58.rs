@@ -0,0 +1,117 @@
+// Monero's Base58 variant: data is split into 8-byte blocks which are each encoded into 11 characters,
+// with the last partial block being encoded into the fewest characters that can hold it. Unlike Bitcoin's
+// Base58, this keeps the encoded length a pure function of the data length.
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const FULL_BLOCK_SIZE: usize = 8;
+const FULL_ENCODED_BLOCK_SIZE: usize = 11;
+const ENCODED_BLOCK_SIZES: [usize; FULL_BLOCK_SIZE + 1] = [0, 2, 3, 5, 6, 7, 9, 10, 11];
+
+pub const fn encoded_len(data_len: usize) -> usize {
+    (data_len / FULL_BLOCK_SIZE) * FULL_ENCODED_BLOCK_SIZE
+        + ENCODED_BLOCK_SIZES[data_len % FULL_BLOCK_SIZE]
+}
+
+fn decoded_block_len(encoded_block_len: usize) -> Option<usize> {
+    ENCODED_BLOCK_SIZES
+        .iter()
+        .position(|&size| size == encoded_block_len)
+}
+
+fn decode_char(c: u8) -> Option<u64> {
+    ALPHABET.iter().position(|&a| a == c).map(|d| d as u64)
+}
+
+fn encode_block(block: &[u8], out: &mut [u8]) {
+    debug_assert!(block.len() <= FULL_BLOCK_SIZE);
+    debug_assert_eq!(out.len(), ENCODED_BLOCK_SIZES[block.len()]);
+
+    // interpret block as a big-endian integer
+    let mut num = block.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64);
+
+    // write digits least significant first, from the back, left-padding with the zero digit
+    for c in out.iter_mut().rev() {
+        *c = ALPHABET[(num % 58) as usize];
+        num /= 58;
+    }
+}
+
+fn decode_block(encoded_block: &[u8], out: &mut [u8]) -> Option<()> {
+    debug_assert_eq!(Some(out.len()), decoded_block_len(encoded_block.len()));
+
+    // accumulate digits into an integer, failing on overflow
+    let mut num: u64 = 0;
+    for &c in encoded_block.iter() {
+        num = num.checked_mul(58)?.checked_add(decode_char(c)?)?;
+    }
+
+    // partial blocks must not encode more bits than they can hold
+    if out.len() < FULL_BLOCK_SIZE && num >> (8 * out.len()) != 0 {
+        return None;
+    }
+
+    // write as big-endian integer
+    out.copy_from_slice(&num.to_be_bytes()[(FULL_BLOCK_SIZE - out.len())..]);
+    Some(())
+}
+
+/// Encode `data` into `out`, which must be exactly `encoded_len(data.len())` characters long
+pub fn encode(data: &[u8], out: &mut [u8]) {
+    assert_eq!(out.len(), encoded_len(data.len()));
+
+    for (block, encoded_block) in data
+        .chunks(FULL_BLOCK_SIZE)
+        .zip(out.chunks_mut(FULL_ENCODED_BLOCK_SIZE))
+    {
+        encode_block(block, encoded_block);
+    }
+}
+
+/// Decode `encoded` into `out`, returning the number of bytes written, or None on malformed input
+pub fn decode(encoded: &[u8], out: &mut [u8]) -> Option<usize> {
+    let num_full_blocks = encoded.len() / FULL_ENCODED_BLOCK_SIZE;
+    let last_block_len = decoded_block_len(encoded.len() % FULL_ENCODED_BLOCK_SIZE)?;
+    let data_len = num_full_blocks * FULL_BLOCK_SIZE + last_block_len;
+    if data_len > out.len() {
+        return None;
+    }
+
+    for (encoded_block, block) in encoded
+        .chunks(FULL_ENCODED_BLOCK_SIZE)
+        .zip(out[..data_len].chunks_mut(FULL_BLOCK_SIZE))
+    {
+        decode_block(encoded_block, block)?;
+    }
+
+    Some(data_len)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::base58::*;
+
+    #[test]
+    fn base58_block_sizes() {
+        let data = [0xffu8; 2 * FULL_BLOCK_SIZE + 3];
+        for n in 0..=data.len() {
+            let mut encoded = [0u8; encoded_len(2 * FULL_BLOCK_SIZE + 3)];
+            let encoded = &mut encoded[..encoded_len(n)];
+            encode(&data[..n], encoded);
+
+            let mut decoded = [0u8; 2 * FULL_BLOCK_SIZE + 3];
+            assert_eq!(Some(n), decode(encoded, &mut decoded));
+            assert_eq!(&data[..n], &decoded[..n]);
+        }
+    }
+
+    #[test]
+    fn base58_reject_malformed() {
+        let mut decoded = [0u8; 16];
+        // 4 characters is not a valid encoded block length
+        assert_eq!(None, decode(b"1111", &mut decoded));
+        // '0' is not in the alphabet
+        assert_eq!(None, decode(b"10", &mut decoded));
+        // "zz" overflows a single byte
+        assert_eq!(None, decode(b"zz", &mut decoded));
+    }
+}
@@ -0,0 +1,121 @@
+use curve25519_dalek::traits::{Identity, IsIdentity};
+use curve25519_dalek::{EdwardsPoint, Scalar};
+use group::GroupEncoding;
+
+use crate::as_crypto::{AsEdwardsPoint, AsScalar};
+use crate::payments::RCTOutputEnoteProposal;
+use crate::*;
+
+#[allow(non_snake_case)]
+fn sum_commitment_points<'a, I>(commitments: I) -> Option<EdwardsPoint>
+where
+    I: IntoIterator<Item = &'a AmountCommitment>,
+{
+    // sum(C)
+    commitments
+        .into_iter()
+        .try_fold(EdwardsPoint::identity(), |sum, C| {
+            Some(sum + EdwardsPoint::from_bytes(&C.as_edwards_ref().0).into_option()?)
+        })
+}
+
+fn sum_scalars<'a, I>(amount_blinding_factors: I) -> Scalar
+where
+    I: IntoIterator<Item = &'a AmountBlindingKey>,
+{
+    amount_blinding_factors
+        .into_iter()
+        .map(|z| z.as_scalar_ref())
+        .sum()
+}
+
+/// sum(C), or None if a commitment isn't a valid point
+pub fn sum_amount_commitments<'a, I>(commitments: I) -> Option<AmountCommitment>
+where
+    I: IntoIterator<Item = &'a AmountCommitment>,
+{
+    Some(AmountCommitment::from_bytes(
+        sum_commitment_points(commitments)?.compress().0,
+    ))
+}
+
+/// sum(z), the blinding factor of the sum of commitments with blinding factors z
+pub fn sum_amount_blinding_factors<'a, I>(amount_blinding_factors: I) -> AmountBlindingKey
+where
+    I: IntoIterator<Item = &'a AmountBlindingKey>,
+{
+    AmountBlindingKey::from_bytes_mod_order(sum_scalars(amount_blinding_factors).to_bytes())
+}
+
+/// Check that the pseudo-output commitments of a transaction's inputs pay exactly for its output
+/// commitments and the fee: sum(C'_in) - sum(C_out) - fee H == 0
+pub fn verify_commitment_balance<'a, I1, I2>(
+    pseudo_output_commitments: I1,
+    output_commitments: I2,
+    fee: Amount,
+) -> bool
+where
+    I1: IntoIterator<Item = &'a AmountCommitment>,
+    I2: IntoIterator<Item = &'a AmountCommitment>,
+{
+    let Some(pseudo_output_sum) = sum_commitment_points(pseudo_output_commitments) else {
+        return false;
+    };
+    let Some(output_sum) = sum_commitment_points(output_commitments) else {
+        return false;
+    };
+    (pseudo_output_sum - output_sum - Scalar::from(fee) * *monero_generators::H).is_identity()
+}
+
+/// Blinding factor of the last pseudo-output commitment which balances the outputs, given the
+/// blinding factors of the other pseudo-outputs: z'_last = sum(k_a_out) - sum(z'_in)
+pub fn make_last_pseudo_output_blinding_factor<'a, I1, I2>(
+    pseudo_output_blinding_factors: I1,
+    output_amount_blinding_factors: I2,
+) -> AmountBlindingKey
+where
+    I1: IntoIterator<Item = &'a AmountBlindingKey>,
+    I2: IntoIterator<Item = &'a AmountBlindingKey>,
+{
+    let z_last =
+        sum_scalars(output_amount_blinding_factors) - sum_scalars(pseudo_output_blinding_factors);
+    AmountBlindingKey::from_bytes_mod_order(z_last.to_bytes())
+}
+
+/// Check that finalized output enote proposals are consistent before proving: each amount
+/// commitment opens to its amount and blinding factor, C_a = k_a G + a H, and the pseudo-outputs
+/// opened by (a, z') pay exactly for them and the fee
+pub fn check_output_enote_proposals_balance(
+    pseudo_output_openings: &[(Amount, AmountBlindingKey)],
+    output_enote_proposals: &[RCTOutputEnoteProposal],
+    fee: Amount,
+) -> bool {
+    let opens_commitment = |output_enote_proposal: &RCTOutputEnoteProposal| {
+        output_enote_proposal.enote.amount_commitment
+            == AmountCommitment::commit(
+                output_enote_proposal.amount,
+                &output_enote_proposal.amount_blinding_factor,
+            )
+    };
+    if !output_enote_proposals.iter().all(opens_commitment) {
+        return false;
+    }
+
+    // sum(a'_in) - sum(a_out) - fee == 0 and sum(z'_in) - sum(k_a_out) == 0, which is the
+    // commitment balance, without the point arithmetic
+    let input_amount = pseudo_output_openings
+        .iter()
+        .try_fold(0, |total: Amount, (amount, _)| total.checked_add(*amount));
+    let output_amount = output_enote_proposals
+        .iter()
+        .try_fold(fee, |total, proposal| total.checked_add(proposal.amount));
+    if input_amount.is_none() || input_amount != output_amount {
+        return false;
+    }
+    sum_scalars(pseudo_output_openings.iter().map(|(_, z)| z))
+        == sum_scalars(
+            output_enote_proposals
+                .iter()
+                .map(|proposal| &proposal.amount_blinding_factor),
+        )
+}
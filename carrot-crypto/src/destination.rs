@@ -19,6 +19,40 @@ pub struct CarrotDestinationV1 {
     pub payment_id: PaymentId,
 }
 
+/// Key derivation scheme of an account's addresses
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AddressDeriveType {
+    /// Carrot-derived account addresses
+    Carrot,
+    /// Legacy CryptoNote account addresses
+    Legacy,
+}
+
+/// Subaddress index $(j_{major}, j_{minor})$, where $(0, 0)$ is the main address
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SubaddressIndex {
+    /// Major index $j_{major}$, a.k.a. account index
+    pub major: u32,
+    /// Minor index $j_{minor}$
+    pub minor: u32,
+}
+
+impl SubaddressIndex {
+    /// Returns whether index belongs to a subaddress, i.e. not the primary/main address
+    pub fn is_subaddress(&self) -> bool {
+        self.major != 0 || self.minor != 0
+    }
+}
+
+/// Subaddress index along with the key derivation scheme of its address
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SubaddressIndexExtended {
+    /// Subaddress index $(j_{major}, j_{minor})$
+    pub index: SubaddressIndex,
+    /// Key derivation scheme of the address at this index
+    pub derive_type: AddressDeriveType,
+}
+
 impl CarrotDestinationV1 {
     /// Construct a primary/main address, Carrot-derived account or otherwise
     pub fn make_main_address(
@@ -45,23 +79,14 @@ impl CarrotDestinationV1 {
             return None;
         }
 
-        // s^j_ap1 = H_32[s_ga](j_major, j_minor)
-        let s_address_index_preimage_1 = s_generate_address_dev
-            .make_address_index_preimage_1(major_index, minor_index)
-            .ok()?;
-
-        // s^j_ap2 = H_32[s^j_ap1](j_major, j_minor, K_s, K_v)
-        let s_address_index_preimage_2 = AddressIndexPreimage2::derive(
-            &s_address_index_preimage_1,
-            major_index,
-            minor_index,
+        // k^j_subscal = H_n[s^j_ap2](K_s)
+        let subaddress_scalar = SubaddressScalarSecret::derive_from_index(
+            s_generate_address_dev,
             account_spend_pubkey,
             account_view_pubkey,
-        );
-
-        // k^j_subscal = H_n[s^j_ap1](K_s)
-        let subaddress_scalar =
-            SubaddressScalarSecret::derive(&s_address_index_preimage_2, account_spend_pubkey);
+            major_index,
+            minor_index,
+        )?;
 
         // K^j_s = k^j_subscal * K_s
         let address_spend_pubkey = AddressSpendPubkey::derive_subaddress_spend_pubkey(
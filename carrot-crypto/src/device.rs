@@ -1,11 +1,13 @@
 use core::fmt::{Debug, Display};
-use curve25519_dalek::EdwardsPoint;
+use curve25519_dalek::{EdwardsPoint, Scalar};
 
 use crate::*;
 
 /// Type of error encountered interacting with devices
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ErrorKind {
+    /// Batched device call given input and output slices of different lengths
+    BatchLengthMismatch,
     /// Device not connected
     NotConnected,
     /// Password needed on-device
@@ -25,6 +27,16 @@ impl Display for Error {
     }
 }
 
+impl Error {
+    pub fn new(kind: ErrorKind) -> Self {
+        Self { kind }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
 /// Result returned from device-based operation
 pub type Result<T> = core::result::Result<T, Error>;
 
@@ -46,6 +58,27 @@ pub trait ViewIncomingKeyDevice {
     #[allow(non_snake_case)]
     fn view_key_scalar_mult_x25519(&self, D: &EnoteEphemeralPubkey) -> Result<MontgomeryECDH>;
 
+    /**
+     * brief: do many X25519 scalar mults against the incoming view key in a single device call
+     *   kvD[i] = k_v * D[i]
+     * return: Ok on success, or an error if any of the scalar mults failed or if D and kvD are
+     *   not the same length
+     */
+    #[allow(non_snake_case)]
+    fn view_key_scalar_mult_x25519_batch(
+        &self,
+        D: &[EnoteEphemeralPubkey],
+        kvD: &mut [MontgomeryECDH],
+    ) -> Result<()> {
+        if D.len() != kvD.len() {
+            return Err(Error::new(ErrorKind::BatchLengthMismatch));
+        }
+        for (D, kvD) in D.iter().zip(kvD.iter_mut()) {
+            *kvD = self.view_key_scalar_mult_x25519(D)?;
+        }
+        Ok(())
+    }
+
     /**
      * brief: make a janus anchor for "special" enotes
      *   anchor_sp = H_16(D_e, input_context, Ko, k_v)
@@ -56,6 +89,21 @@ pub trait ViewIncomingKeyDevice {
         input_context: &InputContext,
         onetime_address: &OutputPubkey,
     ) -> Result<JanusAnchor>;
+
+    /**
+     * brief: make a DLEQ proof that k_v is the discrete log of both k_v B_1 and k_v B_2
+     *   k = H_n[k_v](m, B_1, B_2)
+     *   c = H_n[m](B_1, k_v B_1, B_2, k_v B_2, k B_1, k B_2)
+     *   r = k - c k_v
+     * return: (c, r)
+     */
+    #[allow(non_snake_case)]
+    fn make_view_key_dleq_proof(
+        &self,
+        B_1: &EdwardsPoint,
+        B_2: &EdwardsPoint,
+        message: &[u8; 32],
+    ) -> Result<(Scalar, Scalar)>;
 }
 
 /// Device interface representing a view-balance secret
@@ -29,3 +29,17 @@ pub const GENERATE_ADDRESS_SECRET: &str = "Carrot generate-address secret";
 pub const ADDRESS_INDEX_PREIMAGE_1: &str = "Carrot address index preimage 1";
 pub const ADDRESS_INDEX_PREIMAGE_2: &str = "Carrot address index preimage 2";
 pub const SUBADDRESS_SCALAR: &str = "Carrot subaddress scalar";
+
+// Carrot transaction proof domain separators
+pub const OUT_PROOF_CHALLENGE: &str = "Carrot out proof challenge";
+pub const IN_PROOF_MESSAGE: &str = "Carrot in proof message";
+pub const DLEQ_NONCE: &str = "Carrot DLEQ nonce";
+pub const DLEQ_CHALLENGE: &str = "Carrot DLEQ challenge";
+#[cfg(feature = "alloc")]
+pub const RESERVE_PROOF_CHALLENGE: &str = "Carrot reserve proof challenge";
+
+// Carrot message signing domain separators
+pub const MESSAGE_SIGNATURE_CHALLENGE: &str = "Carrot message signature challenge";
+
+// Legacy CryptoNote domain separators
+pub const LEGACY_SUBADDRESS_EXTENSION: &[u8; 8] = b"SubAddr\0";
@@ -10,6 +10,7 @@ use crate::*;
 // - ephemeral pubkey
 // - tx first key image
 ///
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CarrotEnoteV1 {
     /// K_o
     pub onetime_address: OutputPubkey,
@@ -36,6 +37,7 @@ pub struct CarrotEnoteV1 {
 // - ephemeral pubkey
 // - block index
 ///
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CarrotCoinbaseEnoteV1 {
     /// K_o
     pub onetime_address: OutputPubkey,
@@ -50,3 +52,194 @@ pub struct CarrotCoinbaseEnoteV1 {
     /// block_index
     pub block_index: BlockIndex,
 }
+
+/// Record of an external enote recovered by scanning, with all its recovered secrets
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CarrotExternalEnoteRecordV1 {
+    /// enote which was scanned
+    pub enote: CarrotEnoteV1,
+    /// pid_enc
+    pub encrypted_payment_id: Option<EncryptedPaymentId>,
+    /// k^g_o
+    pub sender_extension_g: OnetimeExtensionG,
+    /// k^t_o
+    pub sender_extension_t: OnetimeExtensionT,
+    /// K^j_s
+    pub address_spend_pubkey: AddressSpendPubkey,
+    /// a
+    pub amount: Amount,
+    /// k_a
+    pub amount_blinding_factor: AmountBlindingKey,
+    /// pid
+    pub payment_id: PaymentId,
+    /// enote_type
+    pub enote_type: CarrotEnoteType,
+}
+
+/// Record of an internal enote recovered by scanning, with all its recovered secrets
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CarrotInternalEnoteRecordV1 {
+    /// enote which was scanned
+    pub enote: CarrotEnoteV1,
+    /// k^g_o
+    pub sender_extension_g: OnetimeExtensionG,
+    /// k^t_o
+    pub sender_extension_t: OnetimeExtensionT,
+    /// K^j_s
+    pub address_spend_pubkey: AddressSpendPubkey,
+    /// a
+    pub amount: Amount,
+    /// k_a
+    pub amount_blinding_factor: AmountBlindingKey,
+    /// enote_type
+    pub enote_type: CarrotEnoteType,
+    /// anchor, used as an arbitrary internal message for internal enotes
+    pub internal_message: JanusAnchor,
+}
+
+/// Record of a coinbase enote recovered by scanning, with all its recovered secrets
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CarrotCoinbaseEnoteRecordV1 {
+    /// enote which was scanned
+    pub enote: CarrotCoinbaseEnoteV1,
+    /// k^g_o
+    pub sender_extension_g: OnetimeExtensionG,
+    /// k^t_o
+    pub sender_extension_t: OnetimeExtensionT,
+    /// K^0_s
+    pub address_spend_pubkey: AddressSpendPubkey,
+}
+
+/// Record of an external enote, tagged with the subaddress index it was received to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CarrotIndexedExternalEnoteRecordV1 {
+    /// record of the scanned enote
+    pub record: CarrotExternalEnoteRecordV1,
+    /// (j_major, j_minor) of K^j_s, and its derive type
+    pub subaddress_index: SubaddressIndexExtended,
+}
+
+/// Record of an internal enote, tagged with the subaddress index it was received to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CarrotIndexedInternalEnoteRecordV1 {
+    /// record of the scanned enote
+    pub record: CarrotInternalEnoteRecordV1,
+    /// (j_major, j_minor) of K^j_s, and its derive type
+    pub subaddress_index: SubaddressIndexExtended,
+}
+
+/// Record of a coinbase enote, tagged with the main address index it was received to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CarrotIndexedCoinbaseEnoteRecordV1 {
+    /// record of the scanned enote
+    pub record: CarrotCoinbaseEnoteRecordV1,
+    /// (0, 0), and the derive type of K^0_s
+    pub subaddress_index: SubaddressIndexExtended,
+}
+
+/// Record of an owned enote, recovered by whichever scan path it was addressed to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CarrotOwnedEnoteRecordV1 {
+    /// Coinbase enote, recovered with the view-incoming key
+    Coinbase(CarrotIndexedCoinbaseEnoteRecordV1),
+    /// Payment or special self-send enote, recovered with the view-incoming key
+    External(CarrotIndexedExternalEnoteRecordV1),
+    /// Internal self-send enote, recovered with the view-balance secret
+    Internal(CarrotIndexedInternalEnoteRecordV1),
+}
+
+impl CarrotOwnedEnoteRecordV1 {
+    /// K_o
+    pub fn onetime_address(&self) -> &OutputPubkey {
+        match self {
+            Self::Coinbase(r) => &r.record.enote.onetime_address,
+            Self::External(r) => &r.record.enote.onetime_address,
+            Self::Internal(r) => &r.record.enote.onetime_address,
+        }
+    }
+
+    /// a
+    pub fn amount(&self) -> Amount {
+        match self {
+            Self::Coinbase(r) => r.record.enote.amount,
+            Self::External(r) => r.record.amount,
+            Self::Internal(r) => r.record.amount,
+        }
+    }
+
+    /// C_a, where coinbase enotes have the clear commitment C_a = G + a H
+    pub fn amount_commitment(&self) -> AmountCommitment {
+        match self {
+            Self::Coinbase(r) => AmountCommitment::clear_commit(r.record.enote.amount),
+            Self::External(r) => r.record.enote.amount_commitment.clone(),
+            Self::Internal(r) => r.record.enote.amount_commitment.clone(),
+        }
+    }
+
+    /// k_a, where coinbase enotes have k_a = 1
+    pub fn amount_blinding_factor(&self) -> AmountBlindingKey {
+        match self {
+            Self::Coinbase(_) => AmountBlindingKey::from(1u64),
+            Self::External(r) => r.record.amount_blinding_factor.clone(),
+            Self::Internal(r) => r.record.amount_blinding_factor.clone(),
+        }
+    }
+
+    /// k^g_o
+    pub fn sender_extension_g(&self) -> &OnetimeExtensionG {
+        match self {
+            Self::Coinbase(r) => &r.record.sender_extension_g,
+            Self::External(r) => &r.record.sender_extension_g,
+            Self::Internal(r) => &r.record.sender_extension_g,
+        }
+    }
+
+    /// k^t_o
+    pub fn sender_extension_t(&self) -> &OnetimeExtensionT {
+        match self {
+            Self::Coinbase(r) => &r.record.sender_extension_t,
+            Self::External(r) => &r.record.sender_extension_t,
+            Self::Internal(r) => &r.record.sender_extension_t,
+        }
+    }
+
+    /// K^j_s
+    pub fn address_spend_pubkey(&self) -> &AddressSpendPubkey {
+        match self {
+            Self::Coinbase(r) => &r.record.address_spend_pubkey,
+            Self::External(r) => &r.record.address_spend_pubkey,
+            Self::Internal(r) => &r.record.address_spend_pubkey,
+        }
+    }
+
+    /// (j_major, j_minor) of K^j_s, and its derive type
+    pub fn subaddress_index(&self) -> &SubaddressIndexExtended {
+        match self {
+            Self::Coinbase(r) => &r.subaddress_index,
+            Self::External(r) => &r.subaddress_index,
+            Self::Internal(r) => &r.subaddress_index,
+        }
+    }
+
+    /// enote_type, where coinbase enotes are payments
+    pub fn enote_type(&self) -> CarrotEnoteType {
+        match self {
+            Self::Coinbase(_) => CarrotEnoteType::Payment,
+            Self::External(r) => r.record.enote_type,
+            Self::Internal(r) => r.record.enote_type,
+        }
+    }
+
+    /// Returns whether the enote is a coinbase enote
+    pub fn is_coinbase(&self) -> bool {
+        matches!(self, Self::Coinbase(_))
+    }
+
+    /// Index of the block a coinbase enote was mined in, which its input context commits to
+    pub fn coinbase_block_index(&self) -> Option<BlockIndex> {
+        match self {
+            Self::Coinbase(r) => Some(r.record.enote.block_index),
+            _ => None,
+        }
+    }
+}
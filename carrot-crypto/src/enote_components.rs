@@ -112,6 +112,17 @@ impl MontgomeryECDH {
         Self(k_view.as_scalar_ref() * enote_ephemeral_pubkey.as_montgomery_ref())
     }
 
+    /// Same result as [`MontgomeryECDH::derive_as_receiver`], but does the scalar mult on the
+    /// Edwards curve when D_e maps to it, which is faster than the Montgomery ladder on the
+    /// vectorized curve25519-dalek backends
+    pub fn derive_as_receiver_via_edwards(
+        k_view: &ViewIncomingKey,
+        enote_ephemeral_pubkey: &EnoteEphemeralPubkey,
+    ) -> Self {
+        // s_sr = k_v D_e
+        Self(scalar_mul_montgomery_via_edwards(k_view, enote_ephemeral_pubkey))
+    }
+
     pub fn derive_as_sender(
         enote_ephemeral_privkey: &EnoteEphemeralKey,
         address_view_pubkey: &AddressViewPubkey,
@@ -127,6 +138,12 @@ impl MontgomeryECDH {
                 * convert_to_montgomery_vartime(address_view_pubkey)?,
         ))
     }
+
+    /// Recover s_sr from its Edwards form S = d_e K^j_v, e.g. the point proven in an out proof
+    pub(crate) fn derive_from_edwards(shared_secret_point: &CompressedEdwardsY) -> Option<Self> {
+        // s_sr = ConvertPointE(S)
+        Some(Self(convert_to_montgomery_vartime(shared_secret_point)?))
+    }
 }
 
 impl InputContext {
@@ -300,6 +317,49 @@ impl OutputPubkey {
     }
 }
 
+impl KeyImageGenerator {
+    pub fn derive(onetime_address: &OutputPubkey) -> Self {
+        // Hp(Ko)
+        Self(hash_to_point(onetime_address).compress())
+    }
+}
+
+impl KeyImage {
+    #[allow(non_snake_case)]
+    pub fn derive_carrot(
+        partial_key_image: &KeyImageGenerator,
+        subaddr_scalar: &SubaddressScalarSecret,
+        sender_extension_g: &OnetimeExtensionG,
+        onetime_address: &OutputPubkey,
+    ) -> Option<Self> {
+        // k^j_subscal L_partial = k^j_subscal k_gi Hp(Ko)
+        let L_address = scalar_mul_key(subaddr_scalar, partial_key_image)?;
+
+        // k^g_o Hp(Ko)
+        let L_extension = scalar_mul_hash_to_point(sender_extension_g, onetime_address);
+
+        // L = (k^j_subscal k_gi + k^g_o) Hp(Ko)
+        Some(Self(add_edwards(&L_address, &L_extension)?))
+    }
+
+    #[allow(non_snake_case)]
+    pub fn derive_legacy(
+        partial_key_image: &KeyImageGenerator,
+        subaddr_extension: &legacy::LegacySubaddressExtension,
+        sender_extension_g: &OnetimeExtensionG,
+        onetime_address: &OutputPubkey,
+    ) -> Option<Self> {
+        // (m + k^g_o) Hp(Ko)
+        let L_extension = scalar_mul_hash_to_point(
+            &(subaddr_extension.as_scalar_ref() + sender_extension_g.as_scalar_ref()),
+            onetime_address,
+        );
+
+        // L = (k_s + m + k^g_o) Hp(Ko)
+        Some(Self(add_edwards(partial_key_image, &L_extension)?))
+    }
+}
+
 impl AddressSpendPubkey {
     pub fn recover_from_extension(
         onetime_address: &OutputPubkey,
@@ -590,6 +650,7 @@ pub fn verify_carrot_normal_janus_protection(
 
 #[cfg(test)]
 mod test {
+    use crate::device::{GenerateImageKeyDevice, ViewIncomingKeyDevice};
     use crate::enote_components::*;
     use crate::unit_testing::*;
 
@@ -660,6 +721,67 @@ mod test {
         );
     }
 
+    #[test]
+    fn converge_make_carrot_uncontextualized_shared_key_receiver_via_edwards() {
+        assert_eq_hex!(
+            "1f848f8384e7a9f217dc9dc2691703cf392eaf6c92931acd0fc840c900d3ed49",
+            MontgomeryECDH::derive_as_receiver_via_edwards(
+                &hex_into!("12624c702b4c1a22fd710a836894ed0705955502e6498e5c6e3ad6f5920bb00f"),
+                &hex_into!("a3c3cdf84fd301cfc4675096f1c896543f2efc1001d899bbab3a0fd137f6a630")
+            )
+        );
+    }
+
+    #[test]
+    fn view_key_scalar_mult_x25519_batch_matches_ladder() {
+        let k_view = ViewIncomingKey::new_random_with_params(&mut rand_core::OsRng, ());
+
+        // random points on the curve and twist, plus the edge cases u = 0 and u = -1
+        let mut enote_ephemeral_pubkeys: [EnoteEphemeralPubkey; 18] = core::array::from_fn(|i| {
+            if i % 2 == 0 {
+                EnoteEphemeralPubkey::new_random_with_params(&mut rand_core::OsRng, ())
+            } else {
+                let mut u = [0u8; 32];
+                rand_core::RngCore::fill_bytes(&mut rand_core::OsRng, &mut u);
+                EnoteEphemeralPubkey(MontgomeryPoint(u))
+            }
+        });
+        enote_ephemeral_pubkeys[16] = EnoteEphemeralPubkey(MontgomeryPoint([0; 32]));
+        let mut minus_one = [0xff; 32];
+        minus_one[0] = 0xec;
+        minus_one[31] = 0x7f;
+        enote_ephemeral_pubkeys[17] = EnoteEphemeralPubkey(MontgomeryPoint(minus_one));
+
+        let mut s_sender_receiver_unctx: [MontgomeryECDH; 18] = Default::default();
+        k_view
+            .view_key_scalar_mult_x25519_batch(
+                &enote_ephemeral_pubkeys,
+                &mut s_sender_receiver_unctx,
+            )
+            .unwrap();
+
+        for (enote_ephemeral_pubkey, s_sender_receiver_unctx) in
+            enote_ephemeral_pubkeys.iter().zip(s_sender_receiver_unctx.iter())
+        {
+            assert_eq!(
+                &MontgomeryECDH::derive_as_receiver(&k_view, enote_ephemeral_pubkey),
+                s_sender_receiver_unctx
+            );
+        }
+
+        // a length mismatch is an error, not a panic
+        assert_eq!(
+            crate::device::ErrorKind::BatchLengthMismatch,
+            k_view
+                .view_key_scalar_mult_x25519_batch(
+                    &enote_ephemeral_pubkeys,
+                    &mut s_sender_receiver_unctx[1..],
+                )
+                .unwrap_err()
+                .kind()
+        );
+    }
+
     #[test]
     fn converge_make_carrot_uncontextualized_shared_key_sender() {
         assert_eq_hex!(
@@ -752,6 +874,100 @@ mod test {
         );
     }
 
+    #[test]
+    fn converge_make_key_image_generator() {
+        // from Monero's tests/crypto/tests.txt hash_to_ec vectors
+        assert_eq_hex!(
+            "52b3f38753b4e13b74624862e253072cf12f745d43fcfafbe8c217701a6e5875",
+            KeyImageGenerator::derive(&hex_into!(
+                "da66e9ba613919dec28ef367a125bb310d6d83fb9052e71034164b6dc4f392d0"
+            ))
+        );
+    }
+
+    #[test]
+    fn converge_make_legacy_key_image() {
+        // from Monero's tests/crypto/tests.txt generate_key_image vectors, where the one-time
+        // address is the public key of its secret key, so there are no extensions
+        let onetime_address: OutputPubkey =
+            hex_into!("e46b60ebfe610b8ba761032018471e5719bb77ea1cd945475c4a4abe7224bfd0");
+        let k_spend: legacy::LegacySpendKey =
+            hex_into!("981d477fb18897fa1f784c89721a9d600bf283f06b89cb018a077f41dcefef0f");
+        assert_eq_hex!(
+            "a637203ec41eab772532d30420eac80612fce8e44f1758bc7e2cb1bdda815887",
+            KeyImage::derive_legacy(
+                &k_spend
+                    .generate_image_scalar_mult_hash_to_point(&onetime_address)
+                    .unwrap(),
+                &hex_into!("0000000000000000000000000000000000000000000000000000000000000000"),
+                &hex_into!("0000000000000000000000000000000000000000000000000000000000000000"),
+                &onetime_address
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn carrot_key_image_matches_opening() {
+        let onetime_address: OutputPubkey =
+            hex_into!("522347147e41f22ebe155abc32b9def985b2e454045c6edd8921ee4253cd4516");
+        let k_generate_image: GenerateImageKey =
+            hex_into!("dabc1ed54dc44f68f67200a1a66ee30b3237f05c2f6dc0dd47e5743431ac800b");
+        let subaddr_scalar: SubaddressScalarSecret =
+            hex_into!("016b3265a2b7b0d05bcffd6f4e87df9fd9b8cd2a39dfc38c4731ca243cca5f09");
+        let sender_extension_g: OnetimeExtensionG =
+            hex_into!("08d8d23620c9d6ecd98c6f7caf4a893fdaf8e4e154a2636fde58dbec426ee202");
+
+        // x = k_gi k^j_subscal + k^g_o
+        let x = k_generate_image.as_scalar_ref() * subaddr_scalar.as_scalar_ref()
+            + sender_extension_g.as_scalar_ref();
+
+        // L = x Hp(Ko)
+        assert_eq!(
+            KeyImage::from_bytes(scalar_mul_hash_to_point(&x, &onetime_address).0),
+            KeyImage::derive_carrot(
+                &k_generate_image
+                    .generate_image_scalar_mult_hash_to_point(&onetime_address)
+                    .unwrap(),
+                &subaddr_scalar,
+                &sender_extension_g,
+                &onetime_address
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn legacy_key_image_matches_opening() {
+        let onetime_address: OutputPubkey =
+            hex_into!("522347147e41f22ebe155abc32b9def985b2e454045c6edd8921ee4253cd4516");
+        let k_spend: legacy::LegacySpendKey =
+            hex_into!("dabc1ed54dc44f68f67200a1a66ee30b3237f05c2f6dc0dd47e5743431ac800b");
+        let subaddr_extension: legacy::LegacySubaddressExtension =
+            hex_into!("016b3265a2b7b0d05bcffd6f4e87df9fd9b8cd2a39dfc38c4731ca243cca5f09");
+        let sender_extension_g: OnetimeExtensionG =
+            hex_into!("08d8d23620c9d6ecd98c6f7caf4a893fdaf8e4e154a2636fde58dbec426ee202");
+
+        // x = k_s + m + k^g_o
+        let x = k_spend.as_scalar_ref()
+            + subaddr_extension.as_scalar_ref()
+            + sender_extension_g.as_scalar_ref();
+
+        // L = x Hp(Ko)
+        assert_eq!(
+            KeyImage::from_bytes(scalar_mul_hash_to_point(&x, &onetime_address).0),
+            KeyImage::derive_legacy(
+                &KeyImageGenerator::from_bytes(
+                    scalar_mul_hash_to_point(&k_spend, &onetime_address).0
+                ),
+                &subaddr_extension,
+                &sender_extension_g,
+                &onetime_address
+            )
+            .unwrap()
+        );
+    }
+
     #[test]
     fn converge_make_carrot_view_tag() {
         assert_eq_hex!(
@@ -0,0 +1,332 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::enote::*;
+use crate::type_macros::*;
+use crate::*;
+
+/// Number of blocks a non-coinbase enote must be buried under before it can be spent
+pub const DEFAULT_SPENDABLE_AGE: BlockIndex = 10;
+/// Number of blocks a coinbase enote must be buried under before it can be spent
+pub const COINBASE_SPENDABLE_AGE: BlockIndex = 60;
+
+/// Hash identifying a block, used to detect reorgs
+pub type BlockId = [u8; 32];
+
+/// Owned enote tracked by an enote store
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CarrotStoredEnoteV1 {
+    /// record of the enote
+    pub record: CarrotOwnedEnoteRecordV1,
+    /// index of the block containing the enote
+    pub block_index: BlockIndex,
+    /// L, if known. view-only wallets can't compute key images, so can't detect spends
+    pub key_image: Option<KeyImage>,
+    /// index of the block containing the transaction which spent the enote, if spent
+    pub spent_block_index: Option<BlockIndex>,
+}
+
+impl CarrotStoredEnoteV1 {
+    /// Lowest top block index at which the enote is unlocked
+    pub fn unlock_block_index(&self) -> BlockIndex {
+        let spendable_age = if self.record.is_coinbase() {
+            COINBASE_SPENDABLE_AGE
+        } else {
+            DEFAULT_SPENDABLE_AGE
+        };
+        self.block_index.saturating_add(spendable_age - 1)
+    }
+
+    /// Returns whether the enote can be spent in the block after the given top block
+    pub fn is_unlocked(&self, top_block_index: BlockIndex) -> bool {
+        top_block_index >= self.unlock_block_index()
+    }
+
+    /// Returns whether the enote has been spent
+    pub fn is_spent(&self) -> bool {
+        self.spent_block_index.is_some()
+    }
+}
+
+/// Sum of amounts of unspent enotes, split by whether they're unlocked yet
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CarrotBalanceV1 {
+    /// amount which can be spent now
+    pub unlocked: Amount,
+    /// amount which can't be spent until more blocks are mined
+    pub locked: Amount,
+}
+
+impl CarrotBalanceV1 {
+    /// Unlocked and locked amount together, or None if it overflows
+    pub fn total(&self) -> Option<Amount> {
+        self.unlocked.checked_add(self.locked)
+    }
+}
+
+/// Store of owned enotes, which tracks their spent status and reports balances. Block ids of
+/// scanned blocks can be recorded as checkpoints, so that the wallet can find where its view of
+/// the chain forks from the daemon's, and roll the store back to there.
+#[derive(Clone, Debug, Default)]
+pub struct CarrotEnoteStore {
+    enotes: Vec<CarrotStoredEnoteV1>,
+    // block index -> block id
+    checkpoints: BTreeMap<BlockIndex, BlockId>,
+    // enotes sharing K_o with a tracked enote, which can never be spent
+    burnt_enotes: Vec<CarrotStoredEnoteV1>,
+    // K_o -> position in enotes
+    onetime_addresses: BTreeMap<[u8; 32], usize>,
+    // L -> position in enotes
+    key_images: BTreeMap<[u8; 32], usize>,
+}
+
+impl CarrotEnoteStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All tracked enotes, spent or not, not including burnt enotes
+    pub fn enotes(&self) -> &[CarrotStoredEnoteV1] {
+        &self.enotes
+    }
+
+    /// Enotes whose one-time address K_o duplicates that of a tracked enote. All enotes sharing
+    /// K_o share a key image, so only one of them can ever be spent: the store tracks the one with
+    /// the largest amount, and sets the others aside here, out of the balance.
+    pub fn burnt_enotes(&self) -> &[CarrotStoredEnoteV1] {
+        &self.burnt_enotes
+    }
+
+    /// Enote with the given one-time address K_o, if in the store
+    pub fn get(&self, onetime_address: &OutputPubkey) -> Option<&CarrotStoredEnoteV1> {
+        let position = self
+            .onetime_addresses
+            .get(&onetime_address.as_edwards_ref().to_bytes())?;
+        Some(&self.enotes[*position])
+    }
+
+    /// Record the id of a scanned block, replacing any previous checkpoint at that index
+    pub fn add_checkpoint(&mut self, block_index: BlockIndex, block_id: BlockId) {
+        self.checkpoints.insert(block_index, block_id);
+    }
+
+    /// Id of the block at the given index, if checkpointed
+    pub fn checkpoint(&self, block_index: BlockIndex) -> Option<&BlockId> {
+        self.checkpoints.get(&block_index)
+    }
+
+    /// Highest checkpointed block index and its block id
+    pub fn top_checkpoint(&self) -> Option<(BlockIndex, &BlockId)> {
+        self.checkpoints
+            .last_key_value()
+            .map(|(block_index, block_id)| (*block_index, block_id))
+    }
+
+    /// Start tracking an owned enote received in the given block, with its key image if known.
+    /// Returns whether the enote is now the tracked enote for its one-time address K_o. If another
+    /// enote with the same K_o was added before, the one with the smaller amount is moved to the
+    /// burnt enotes, unless the tracked one was already spent. Re-adding an identical enote, or a
+    /// coinbase enote committing to a different block index, does nothing and returns false.
+    pub fn add_enote(
+        &mut self,
+        record: CarrotOwnedEnoteRecordV1,
+        block_index: BlockIndex,
+        key_image: Option<KeyImage>,
+    ) -> bool {
+        if record
+            .coinbase_block_index()
+            .is_some_and(|coinbase_block_index| coinbase_block_index != block_index)
+        {
+            return false;
+        }
+
+        self.insert_enote(CarrotStoredEnoteV1 {
+            record,
+            block_index,
+            key_image,
+            spent_block_index: None,
+        })
+    }
+
+    /// Set the key image of an already tracked enote, e.g. once the wallet gains the keys to
+    /// compute it. Returns false if the one-time address isn't tracked.
+    pub fn set_key_image(&mut self, onetime_address: &OutputPubkey, key_image: KeyImage) -> bool {
+        let Some(position) = self
+            .onetime_addresses
+            .get(&onetime_address.as_edwards_ref().to_bytes())
+            .copied()
+        else {
+            return false;
+        };
+        self.insert_key_image(position, key_image);
+        true
+    }
+
+    /// Mark tracked enotes as spent by the inputs of a transaction in the given block. Returns the
+    /// number of enotes newly marked as spent.
+    pub fn process_spent_key_images(
+        &mut self,
+        key_images: &[KeyImage],
+        block_index: BlockIndex,
+    ) -> usize {
+        let mut num_spent = 0;
+        for key_image in key_images.iter() {
+            let Some(position) = self.key_images.get(&key_image.as_edwards_ref().to_bytes()) else {
+                continue;
+            };
+            let enote = &mut self.enotes[*position];
+            if enote.spent_block_index.is_none() {
+                enote.spent_block_index = Some(block_index);
+                num_spent += 1;
+            }
+        }
+        num_spent
+    }
+
+    /// Undo everything seen in blocks after the given block index, after a reorg: enotes received
+    /// in those blocks are forgotten, enotes spent in them are marked unspent again, and their
+    /// checkpoints are dropped. Returns the number of enotes removed.
+    pub fn rollback(&mut self, block_index: BlockIndex) -> usize {
+        self.checkpoints.retain(|index, _| *index <= block_index);
+
+        // positions shift, and burnt enotes may become spendable again, so re-add the enotes which
+        // survive the reorg from scratch, tracked ones first
+        let num_enotes = self.enotes.len() + self.burnt_enotes.len();
+        let enotes = core::mem::take(&mut self.enotes);
+        let burnt_enotes = core::mem::take(&mut self.burnt_enotes);
+        self.onetime_addresses.clear();
+        self.key_images.clear();
+        for mut enote in enotes.into_iter().chain(burnt_enotes) {
+            if enote.block_index > block_index {
+                continue;
+            }
+            if enote
+                .spent_block_index
+                .is_some_and(|spent_block_index| spent_block_index > block_index)
+            {
+                enote.spent_block_index = None;
+            }
+            self.insert_enote(enote);
+        }
+
+        num_enotes - self.enotes.len() - self.burnt_enotes.len()
+    }
+
+    /// Unspent enotes which can be spent in the block after the given top block
+    pub fn spendable_enotes(
+        &self,
+        top_block_index: BlockIndex,
+    ) -> impl Iterator<Item = &CarrotStoredEnoteV1> {
+        self.enotes
+            .iter()
+            .filter(move |enote| !enote.is_spent() && enote.is_unlocked(top_block_index))
+    }
+
+    /// Balance of the whole wallet, or None if it overflows
+    pub fn balance(&self, top_block_index: BlockIndex) -> Option<CarrotBalanceV1> {
+        self.balance_where(top_block_index, |_| true)
+    }
+
+    /// Balance of an account, i.e. all subaddresses with the given major index and derive type, or
+    /// None if it overflows
+    pub fn account_balance(
+        &self,
+        major_index: u32,
+        derive_type: AddressDeriveType,
+        top_block_index: BlockIndex,
+    ) -> Option<CarrotBalanceV1> {
+        self.balance_where(top_block_index, |subaddress_index| {
+            subaddress_index.index.major == major_index
+                && subaddress_index.derive_type == derive_type
+        })
+    }
+
+    /// Balance of a single subaddress, or None if it overflows. Carrot and legacy subaddresses at
+    /// the same (j_major, j_minor) are different addresses, so are counted separately.
+    pub fn subaddress_balance(
+        &self,
+        index: &SubaddressIndexExtended,
+        top_block_index: BlockIndex,
+    ) -> Option<CarrotBalanceV1> {
+        self.balance_where(top_block_index, |subaddress_index| {
+            subaddress_index == index
+        })
+    }
+
+    fn balance_where<F>(
+        &self,
+        top_block_index: BlockIndex,
+        mut filter: F,
+    ) -> Option<CarrotBalanceV1>
+    where
+        F: FnMut(&SubaddressIndexExtended) -> bool,
+    {
+        let mut balance = CarrotBalanceV1::default();
+        for enote in self.enotes.iter() {
+            if enote.is_spent() || !filter(enote.record.subaddress_index()) {
+                continue;
+            }
+            let amount = enote.record.amount();
+            if enote.is_unlocked(top_block_index) {
+                balance.unlocked = balance.unlocked.checked_add(amount)?;
+            } else {
+                balance.locked = balance.locked.checked_add(amount)?;
+            }
+        }
+        Some(balance)
+    }
+
+    fn insert_enote(&mut self, mut enote: CarrotStoredEnoteV1) -> bool {
+        let key_image = enote.key_image.take();
+        let onetime_address = enote.record.onetime_address().as_edwards_ref().to_bytes();
+
+        let Some(position) = self.onetime_addresses.get(&onetime_address).copied() else {
+            let position = self.enotes.len();
+            self.onetime_addresses.insert(onetime_address, position);
+            self.enotes.push(enote);
+            if let Some(key_image) = key_image {
+                self.insert_key_image(position, key_image);
+            }
+            return true;
+        };
+
+        let tracked = &self.enotes[position];
+        let is_same_enote = |other: &CarrotStoredEnoteV1| {
+            other.record == enote.record && other.block_index == enote.block_index
+        };
+        if is_same_enote(tracked) || self.burnt_enotes.iter().any(is_same_enote) {
+            return false;
+        }
+
+        // K_o reuse: only the tracked enote can be spent, and it should be the one with the
+        // largest amount, but the balance must never count more than one of them
+        if tracked.is_spent() || enote.record.amount() <= tracked.record.amount() {
+            enote.key_image = key_image.or_else(|| tracked.key_image.clone());
+            self.burnt_enotes.push(enote);
+            return false;
+        }
+        let key_image = key_image.or_else(|| tracked.key_image.clone());
+        let mut burnt = core::mem::replace(&mut self.enotes[position], enote);
+        if let Some(key_image) = burnt.key_image.take() {
+            self.key_images
+                .remove(&key_image.as_edwards_ref().to_bytes());
+            burnt.key_image = Some(key_image);
+        }
+        self.burnt_enotes.push(burnt);
+        if let Some(key_image) = key_image {
+            self.insert_key_image(position, key_image);
+        }
+        true
+    }
+
+    fn insert_key_image(&mut self, position: usize, key_image: KeyImage) {
+        if let Some(old_key_image) = &self.enotes[position].key_image {
+            self.key_images
+                .remove(&old_key_image.as_edwards_ref().to_bytes());
+        }
+        self.key_images
+            .insert(key_image.as_edwards_ref().to_bytes(), position);
+        self.enotes[position].key_image = Some(key_image);
+    }
+}
@@ -0,0 +1,154 @@
+use crate::output_set_finalization::{AdditionalOutputType, get_additional_output_type};
+use crate::payments::*;
+use crate::*;
+
+/// Linear model of transaction weight, and the fee paid per unit of weight
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeModelV1 {
+    /// fee paid per unit of weight
+    pub fee_per_weight: Amount,
+    /// weight of a transaction without inputs and outputs
+    pub base_weight: u64,
+    /// weight added by each input
+    pub weight_per_input: u64,
+    /// weight added by each output
+    pub weight_per_output: u64,
+}
+
+impl FeeModelV1 {
+    /// Weight of a transaction with the given number of inputs and outputs
+    pub fn estimate_weight(&self, num_inputs: usize, num_outputs: usize) -> Result<u64> {
+        let input_weight = (num_inputs as u64).checked_mul(self.weight_per_input);
+        let output_weight = (num_outputs as u64).checked_mul(self.weight_per_output);
+        input_weight
+            .zip(output_weight)
+            .and_then(|(input_weight, output_weight)| {
+                self.base_weight
+                    .checked_add(input_weight)?
+                    .checked_add(output_weight)
+            })
+            .ok_or(Error::new(ErrorKind::AmountOverflow))
+    }
+
+    /// Fee of a transaction with the given number of inputs and outputs
+    pub fn estimate_fee(&self, num_inputs: usize, num_outputs: usize) -> Result<Amount> {
+        self.estimate_weight(num_inputs, num_outputs)?
+            .checked_mul(self.fee_per_weight)
+            .ok_or(Error::new(ErrorKind::AmountOverflow))
+    }
+}
+
+/// Fee and change of a transaction, with the additional output needed to complete its output set
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CarrotTxFeeV1 {
+    /// fee paid by the transaction
+    pub fee: Amount,
+    /// amount of the change output: input amount - sum of payments - fee
+    pub change_amount: Amount,
+    /// type of the change or dummy output to add to the payment proposals, if any
+    pub additional_output_type: Option<AdditionalOutputType>,
+    /// number of outputs, including the additional output
+    pub num_outputs: usize,
+}
+
+// sum of amounts, failing on u64 overflow
+fn sum_amounts<I: IntoIterator<Item = Amount>>(amounts: I) -> Result<Amount> {
+    amounts
+        .into_iter()
+        .try_fold(0, |total: Amount, amount| total.checked_add(amount))
+        .ok_or(Error::new(ErrorKind::AmountOverflow))
+}
+
+/// Compute the fee and change of a transaction spending inputs with the given amounts to the
+/// given payment proposals, accounting for the weight of the change or dummy output which
+/// [`get_additional_output_type`] will add. If the inputs exactly cover the payments and the fee
+/// without a change output, none is added. If the amount left over without a change output is
+/// positive, but too small to pay for the change output's weight, it's added to the fee instead.
+pub fn get_tx_fee_and_change(
+    input_amounts: &[Amount],
+    normal_payment_proposals: &[CarrotPaymentProposalV1],
+    selfsend_payment_proposals: &[CarrotPaymentProposalSelfSendV1],
+    fee_model: &FeeModelV1,
+) -> Result<CarrotTxFeeV1> {
+    let input_amount = sum_amounts(input_amounts.iter().copied())?;
+    let output_amount = sum_amounts(
+        normal_payment_proposals
+            .iter()
+            .map(|proposal| proposal.amount)
+            .chain(
+                selfsend_payment_proposals
+                    .iter()
+                    .map(|proposal| proposal.amount),
+            ),
+    )?;
+    let have_payment_type_selfsend = selfsend_payment_proposals
+        .iter()
+        .any(|proposal| proposal.enote_type == CarrotEnoteType::Payment);
+
+    let get_fee_and_change = |need_change_output: bool| -> Result<CarrotTxFeeV1> {
+        let additional_output_type = get_additional_output_type(
+            normal_payment_proposals.len(),
+            selfsend_payment_proposals.len(),
+            need_change_output,
+            have_payment_type_selfsend,
+        );
+        let num_outputs = normal_payment_proposals.len()
+            + selfsend_payment_proposals.len()
+            + additional_output_type.is_some() as usize;
+        let fee = fee_model.estimate_fee(input_amounts.len(), num_outputs)?;
+
+        // change = sum(a_in) - sum(a_out) - fee
+        let spent_amount = output_amount
+            .checked_add(fee)
+            .ok_or(Error::new(ErrorKind::AmountOverflow))?;
+        let change_amount = input_amount
+            .checked_sub(spent_amount)
+            .ok_or(Error::new(ErrorKind::InsufficientFunds))?;
+
+        Ok(CarrotTxFeeV1 {
+            fee,
+            change_amount,
+            additional_output_type,
+            num_outputs,
+        })
+    };
+
+    // a change output can only make the fee larger, so if the inputs don't cover the payments and
+    // fee without one, they never will
+    let tx_fee = get_fee_and_change(false)?;
+    if tx_fee.change_amount == 0 {
+        return Ok(tx_fee);
+    }
+
+    // if the leftover amount doesn't pay for the change output's weight, it goes to the fee instead
+    let tx_fee_without_change = CarrotTxFeeV1 {
+        fee: tx_fee.fee + tx_fee.change_amount,
+        change_amount: 0,
+        ..tx_fee
+    };
+    match get_fee_and_change(true) {
+        Ok(tx_fee) if tx_fee.change_amount != 0 => Ok(tx_fee),
+        Ok(_) => Ok(tx_fee_without_change),
+        Err(error) if error.kind() == ErrorKind::InsufficientFunds => Ok(tx_fee_without_change),
+        Err(error) => Err(error),
+    }
+}
+
+/// Check that the inputs exactly pay for the outputs and the fee: sum(a_in) = sum(a_out) + fee
+pub fn check_tx_amounts_balance(
+    input_amounts: &[Amount],
+    output_enote_proposals: &[RCTOutputEnoteProposal],
+    fee: Amount,
+) -> Result<()> {
+    let input_amount = sum_amounts(input_amounts.iter().copied())?;
+    let output_amount = sum_amounts(
+        output_enote_proposals
+            .iter()
+            .map(|proposal| proposal.amount)
+            .chain([fee]),
+    )?;
+    if input_amount != output_amount {
+        return Err(Error::new(ErrorKind::UnbalancedAmounts));
+    }
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use blake2::{Blake2bVarCore, digest};
 use curve25519_dalek::Scalar;
 use digest::core_api::{OutputSizeUser, TruncSide, UpdateCore, VariableOutputCore};
+use sha3::{Digest, Keccak256};
 use typenum::Unsigned;
 
 use crate::domain_separators::PERSONAL_STRING;
@@ -50,3 +51,13 @@ pub fn derive_scalar(data: &[u8], key: &[u8]) -> Scalar {
     let unreduced64_scalar = derive_bytes_64(data, key);
     Scalar::from_bytes_mod_order_wide(&unreduced64_scalar)
 }
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    // legacy CryptoNote hash: H(x) = Keccak256(x)
+    Keccak256::digest(data).into()
+}
+
+pub fn derive_scalar_legacy(data: &[u8]) -> Scalar {
+    // legacy CryptoNote hash-to-scalar: H_n(x) = Keccak256(x) mod l
+    Scalar::from_bytes_mod_order(keccak256(data))
+}
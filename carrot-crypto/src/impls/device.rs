@@ -1,5 +1,6 @@
 use crate::as_crypto::AsScalar;
 use crate::device::*;
+use crate::math_utils::scalar_mul_hash_to_point;
 use crate::*;
 
 impl ViewIncomingKeyDevice for ViewIncomingKey {
@@ -16,6 +17,21 @@ impl ViewIncomingKeyDevice for ViewIncomingKey {
         Ok(MontgomeryECDH::derive_as_receiver(self, D))
     }
 
+    #[allow(non_snake_case)]
+    fn view_key_scalar_mult_x25519_batch(
+        &self,
+        D: &[EnoteEphemeralPubkey],
+        kvD: &mut [MontgomeryECDH],
+    ) -> Result<()> {
+        if D.len() != kvD.len() {
+            return Err(Error::new(ErrorKind::BatchLengthMismatch));
+        }
+        for (D, kvD) in D.iter().zip(kvD.iter_mut()) {
+            *kvD = MontgomeryECDH::derive_as_receiver_via_edwards(self, D);
+        }
+        Ok(())
+    }
+
     fn make_janus_anchor_special(
         &self,
         enote_ephemeral_pubkey: &EnoteEphemeralPubkey,
@@ -29,6 +45,21 @@ impl ViewIncomingKeyDevice for ViewIncomingKey {
             self,
         ))
     }
+
+    #[allow(non_snake_case)]
+    fn make_view_key_dleq_proof(
+        &self,
+        B_1: &curve25519_dalek::EdwardsPoint,
+        B_2: &curve25519_dalek::EdwardsPoint,
+        message: &[u8; 32],
+    ) -> Result<(curve25519_dalek::Scalar, curve25519_dalek::Scalar)> {
+        Ok(crate::tx_proofs::make_dleq_proof(
+            self.as_scalar_ref(),
+            B_1,
+            B_2,
+            message,
+        ))
+    }
 }
 
 impl ViewBalanceSecretDevice for ViewBalanceSecret {
@@ -70,3 +101,153 @@ impl GenerateAddressSecretDevice for GenerateAddressSecret {
         ))
     }
 }
+
+impl GenerateImageKeyDevice for GenerateImageKey {
+    fn generate_image_scalar_mult_hash_to_point(
+        &self,
+        onetime_address: &OutputPubkey,
+    ) -> Result<KeyImageGenerator> {
+        // L_partial = k_gi Hp(K_o)
+        Ok(KeyImageGenerator::from_bytes(
+            scalar_mul_hash_to_point(self, onetime_address).0,
+        ))
+    }
+}
+
+impl GenerateImageKeyDevice for crate::legacy::LegacySpendKey {
+    fn generate_image_scalar_mult_hash_to_point(
+        &self,
+        onetime_address: &OutputPubkey,
+    ) -> Result<KeyImageGenerator> {
+        // L_partial = k_s Hp(K_o)
+        Ok(KeyImageGenerator::from_bytes(
+            scalar_mul_hash_to_point(self, onetime_address).0,
+        ))
+    }
+}
+
+// delegate a device trait to the key held by an account type
+macro_rules! impl_view_incoming_key_device_for_account {
+    ($account_type:ty) => {
+        impl ViewIncomingKeyDevice for $account_type {
+            #[allow(non_snake_case)]
+            fn view_key_scalar_mult_ed25519(
+                &self,
+                P: &curve25519_dalek::EdwardsPoint,
+            ) -> Result<curve25519_dalek::EdwardsPoint> {
+                self.view_incoming_key().view_key_scalar_mult_ed25519(P)
+            }
+
+            #[allow(non_snake_case)]
+            fn view_key_scalar_mult_x25519(
+                &self,
+                D: &EnoteEphemeralPubkey,
+            ) -> Result<MontgomeryECDH> {
+                self.view_incoming_key().view_key_scalar_mult_x25519(D)
+            }
+
+            #[allow(non_snake_case)]
+            fn view_key_scalar_mult_x25519_batch(
+                &self,
+                D: &[EnoteEphemeralPubkey],
+                kvD: &mut [MontgomeryECDH],
+            ) -> Result<()> {
+                self.view_incoming_key()
+                    .view_key_scalar_mult_x25519_batch(D, kvD)
+            }
+
+            fn make_janus_anchor_special(
+                &self,
+                enote_ephemeral_pubkey: &EnoteEphemeralPubkey,
+                input_context: &InputContext,
+                onetime_address: &OutputPubkey,
+            ) -> Result<JanusAnchor> {
+                self.view_incoming_key().make_janus_anchor_special(
+                    enote_ephemeral_pubkey,
+                    input_context,
+                    onetime_address,
+                )
+            }
+
+            #[allow(non_snake_case)]
+            fn make_view_key_dleq_proof(
+                &self,
+                B_1: &curve25519_dalek::EdwardsPoint,
+                B_2: &curve25519_dalek::EdwardsPoint,
+                message: &[u8; 32],
+            ) -> Result<(curve25519_dalek::Scalar, curve25519_dalek::Scalar)> {
+                self.view_incoming_key()
+                    .make_view_key_dleq_proof(B_1, B_2, message)
+            }
+        }
+    };
+}
+
+macro_rules! impl_view_balance_secret_device_for_account {
+    ($account_type:ty) => {
+        impl ViewBalanceSecretDevice for $account_type {
+            fn make_internal_view_tag(
+                &self,
+                input_context: &InputContext,
+                onetime_address: &OutputPubkey,
+            ) -> Result<ViewTag> {
+                self.view_balance_secret()
+                    .make_internal_view_tag(input_context, onetime_address)
+            }
+
+            fn make_internal_sender_receiver_secret(
+                &self,
+                enote_ephemeral_pubkey: &EnoteEphemeralPubkey,
+                input_context: &InputContext,
+            ) -> Result<SenderReceiverSecret> {
+                self.view_balance_secret()
+                    .make_internal_sender_receiver_secret(enote_ephemeral_pubkey, input_context)
+            }
+        }
+    };
+}
+
+macro_rules! impl_generate_address_secret_device_for_account {
+    ($account_type:ty) => {
+        impl GenerateAddressSecretDevice for $account_type {
+            fn make_address_index_preimage_1(
+                &self,
+                major_index: u32,
+                minor_index: u32,
+            ) -> Result<AddressIndexPreimage1> {
+                self.generate_address_secret()
+                    .make_address_index_preimage_1(major_index, minor_index)
+            }
+        }
+    };
+}
+
+macro_rules! impl_generate_image_key_device_for_account {
+    ($account_type:ty, $getter:ident) => {
+        impl GenerateImageKeyDevice for $account_type {
+            fn generate_image_scalar_mult_hash_to_point(
+                &self,
+                onetime_address: &OutputPubkey,
+            ) -> Result<KeyImageGenerator> {
+                self.$getter()
+                    .generate_image_scalar_mult_hash_to_point(onetime_address)
+            }
+        }
+    };
+}
+
+impl_view_incoming_key_device_for_account!(CarrotAccount);
+impl_view_balance_secret_device_for_account!(CarrotAccount);
+impl_generate_address_secret_device_for_account!(CarrotAccount);
+impl_generate_image_key_device_for_account!(CarrotAccount, generate_image_key);
+
+impl_view_incoming_key_device_for_account!(ViewBalanceAccount);
+impl_view_balance_secret_device_for_account!(ViewBalanceAccount);
+impl_generate_address_secret_device_for_account!(ViewBalanceAccount);
+
+impl_view_incoming_key_device_for_account!(ViewIncomingAccount);
+
+impl_generate_address_secret_device_for_account!(AddressGeneratorAccount);
+
+impl_view_incoming_key_device_for_account!(crate::legacy::LegacyAccount);
+impl_generate_image_key_device_for_account!(crate::legacy::LegacyAccount, spend_key);
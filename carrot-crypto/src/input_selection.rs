@@ -0,0 +1,209 @@
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+use crate::enote_store::{CarrotEnoteStore, CarrotStoredEnoteV1};
+use crate::payments::{Error, ErrorKind, Result};
+use crate::type_macros::*;
+use crate::*;
+
+/// Strategy for picking which inputs to spend
+pub trait InputSelectionStrategy {
+    /// Pick candidates, by their index in `amounts`, whose amounts add up to at least the target
+    /// amount, or return None if that's impossible
+    fn select(&self, amounts: &[Amount], target_amount: Amount) -> Option<Vec<usize>>;
+}
+
+/// Spend the largest inputs first, which minimizes the number of inputs
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GreedyInputSelector;
+
+impl InputSelectionStrategy for GreedyInputSelector {
+    fn select(&self, amounts: &[Amount], target_amount: Amount) -> Option<Vec<usize>> {
+        let mut candidates: Vec<usize> = (0..amounts.len()).collect();
+        candidates.sort_by_key(|i| Reverse(amounts[*i]));
+
+        let mut selected = Vec::new();
+        let mut selected_amount: Amount = 0;
+        for i in candidates {
+            if selected_amount >= target_amount {
+                break;
+            }
+            selected.push(i);
+            selected_amount = selected_amount.saturating_add(amounts[i]);
+        }
+        (selected_amount >= target_amount).then_some(selected)
+    }
+}
+
+/// Spend the inputs which leave the least change: while no single input covers the amount still
+/// needed, spend the largest, then finish with the smallest input which covers the rest
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MinChangeInputSelector;
+
+impl InputSelectionStrategy for MinChangeInputSelector {
+    fn select(&self, amounts: &[Amount], target_amount: Amount) -> Option<Vec<usize>> {
+        let mut unselected: Vec<usize> = (0..amounts.len()).collect();
+        let mut selected = Vec::new();
+        let mut needed_amount = target_amount;
+        loop {
+            let best_single = unselected
+                .iter()
+                .enumerate()
+                .filter(|(_, i)| amounts[**i] >= needed_amount)
+                .min_by_key(|(_, i)| amounts[**i]);
+            if let Some((j, _)) = best_single {
+                selected.push(unselected.swap_remove(j));
+                return Some(selected);
+            }
+
+            let (j, _) = unselected
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, i)| amounts[**i])?;
+            let i = unselected.swap_remove(j);
+            needed_amount -= amounts[i];
+            selected.push(i);
+        }
+    }
+}
+
+/// Never spend inputs worth less than a dust threshold, which would cost more in fees than they
+/// are worth, and pick among the others with another strategy
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AvoidDustInputSelector<S> {
+    /// inputs with amounts below this aren't spent
+    pub dust_threshold: Amount,
+    /// strategy used on the remaining inputs
+    pub strategy: S,
+}
+
+impl<S: InputSelectionStrategy> InputSelectionStrategy for AvoidDustInputSelector<S> {
+    fn select(&self, amounts: &[Amount], target_amount: Amount) -> Option<Vec<usize>> {
+        let candidates: Vec<usize> = (0..amounts.len())
+            .filter(|i| amounts[*i] >= self.dust_threshold)
+            .collect();
+        let candidate_amounts: Vec<Amount> = candidates.iter().map(|i| amounts[*i]).collect();
+        let selected = self.strategy.select(&candidate_amounts, target_amount)?;
+        Some(selected.into_iter().map(|i| candidates[i]).collect())
+    }
+}
+
+/// Inputs picked for a transaction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CarrotInputSelectionV1 {
+    /// selected enotes, sorted by key image in descending order, as the inputs of a transaction
+    pub inputs: Vec<CarrotStoredEnoteV1>,
+    /// sum of the amounts of the selected enotes
+    pub input_amount: Amount,
+}
+
+impl CarrotInputSelectionV1 {
+    /// L_0, the key image of the first input of the transaction
+    pub fn first_key_image(&self) -> Option<&KeyImage> {
+        self.inputs.first()?.key_image.as_ref()
+    }
+
+    /// Input context of the transaction's outputs: "R" || L_0
+    pub fn input_context(&self) -> Option<InputContext> {
+        Some(InputContext::new_ringct(self.first_key_image()?))
+    }
+}
+
+/// Select unspent, unlocked enotes from the store, worth at least the target amount. The target
+/// should include the fee. Only enotes with known key images can be spent, and coinbase enotes
+/// are only candidates once they've reached their longer unlock age. If an enote type is given,
+/// only enotes of that type are candidates, e.g. to consolidate change.
+pub fn select_inputs<S: InputSelectionStrategy>(
+    enote_store: &CarrotEnoteStore,
+    top_block_index: BlockIndex,
+    target_amount: Amount,
+    enote_type: Option<CarrotEnoteType>,
+    strategy: &S,
+) -> Result<CarrotInputSelectionV1> {
+    let candidates: Vec<&CarrotStoredEnoteV1> = enote_store
+        .spendable_enotes(top_block_index)
+        .filter(|enote| enote.key_image.is_some())
+        .filter(|enote| enote_type.is_none_or(|t| enote.record.enote_type() == t))
+        .collect();
+    let amounts: Vec<Amount> = candidates
+        .iter()
+        .map(|enote| enote.record.amount())
+        .collect();
+
+    let mut selected = strategy
+        .select(&amounts, target_amount)
+        .filter(|selected| !selected.is_empty())
+        .ok_or(Error::new(ErrorKind::InsufficientFunds))?;
+    selected.sort_unstable();
+    selected.dedup();
+
+    let mut inputs: Vec<CarrotStoredEnoteV1> =
+        selected.iter().map(|i| candidates[*i].clone()).collect();
+    let input_amount = selected
+        .iter()
+        .try_fold(0, |total: Amount, i| total.checked_add(amounts[*i]))
+        .ok_or(Error::new(ErrorKind::AmountOverflow))?;
+    if input_amount < target_amount {
+        return Err(Error::new(ErrorKind::InsufficientFunds));
+    }
+
+    // transaction inputs are sorted by key image in descending order
+    inputs.sort_by_key(|input| {
+        Reverse(
+            input
+                .key_image
+                .as_ref()
+                .map(|key_image| key_image.as_edwards_ref().to_bytes()),
+        )
+    });
+
+    Ok(CarrotInputSelectionV1 {
+        inputs,
+        input_amount,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const AMOUNTS: [Amount; 6] = [50, 3, 700, 120, 1, 400];
+
+    fn selected_amounts(selected: Option<Vec<usize>>) -> Option<Vec<Amount>> {
+        selected.map(|selected| selected.into_iter().map(|i| AMOUNTS[i]).collect())
+    }
+
+    #[test]
+    fn greedy_input_selector() {
+        let select =
+            |target_amount| selected_amounts(GreedyInputSelector.select(&AMOUNTS, target_amount));
+        assert_eq!(Some(alloc::vec![700]), select(100));
+        assert_eq!(Some(alloc::vec![700, 400]), select(701));
+        assert_eq!(Some(alloc::vec![700, 400, 120, 50, 3, 1]), select(1274));
+        assert_eq!(None, select(1275));
+    }
+
+    #[test]
+    fn min_change_input_selector() {
+        let select = |target_amount| {
+            selected_amounts(MinChangeInputSelector.select(&AMOUNTS, target_amount))
+        };
+        assert_eq!(Some(alloc::vec![120]), select(100));
+        assert_eq!(Some(alloc::vec![3]), select(2));
+        assert_eq!(Some(alloc::vec![700, 1]), select(701));
+        assert_eq!(Some(alloc::vec![700, 400, 120, 50, 3]), select(1273));
+        assert_eq!(None, select(1275));
+    }
+
+    #[test]
+    fn avoid_dust_input_selector() {
+        let avoid_dust = AvoidDustInputSelector {
+            dust_threshold: 10,
+            strategy: MinChangeInputSelector,
+        };
+        let select = |target_amount| selected_amounts(avoid_dust.select(&AMOUNTS, target_amount));
+        assert_eq!(Some(alloc::vec![50]), select(2));
+        assert_eq!(Some(alloc::vec![700, 400, 120, 50]), select(1270));
+        assert_eq!(None, select(1271));
+    }
+}
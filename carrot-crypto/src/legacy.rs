@@ -0,0 +1,295 @@
+use zeroize::ZeroizeOnDrop;
+
+use crate::destination::*;
+use crate::domain_separators;
+use crate::hash_functions::*;
+use crate::math_utils::*;
+use crate::opening::*;
+use crate::scan::make_legacy_key_image;
+use crate::type_macros::*;
+use crate::*;
+
+define_tiny_scalar_type! {LegacySpendKey, "Spend key for a legacy CryptoNote account"}
+define_tiny_scalar_type! {LegacySubaddressExtension, "Subaddress extension scalar for a legacy CryptoNote address"}
+
+impl ViewIncomingKey {
+    /// Derive legacy CryptoNote view key from the spend key
+    pub fn derive_legacy(k_spend: &LegacySpendKey) -> Self {
+        // k_v = H_n(k_s)
+        Self::from_bytes_mod_order(derive_scalar_legacy(k_spend.as_bytes()).to_bytes())
+    }
+}
+
+impl LegacySubaddressExtension {
+    /// Derive legacy CryptoNote subaddress extension scalar from the view key and index
+    pub fn derive(k_view: &ViewIncomingKey, major_index: u32, minor_index: u32) -> Self {
+        // m = 0 for the main address
+        if major_index == 0 && minor_index == 0 {
+            return Self::default();
+        }
+
+        // m = H_n("SubAddr" || IntToBytes8(0) || k_v || IntToBytes32(j_major) || IntToBytes32(j_minor))
+        let mut data = [0u8; 8 + 32 + 4 + 4];
+        data[0..8].copy_from_slice(domain_separators::LEGACY_SUBADDRESS_EXTENSION);
+        data[8..40].copy_from_slice(k_view.as_bytes());
+        data[40..44].copy_from_slice(&major_index.to_le_bytes());
+        data[44..48].copy_from_slice(&minor_index.to_le_bytes());
+        Self(derive_scalar_legacy(&data))
+    }
+}
+
+impl AddressSpendPubkey {
+    /// Derive legacy CryptoNote account spend pubkey from the spend key
+    pub fn derive_legacy_account_spend_pubkey(k_spend: &LegacySpendKey) -> Self {
+        // K_s = k_s G
+        Self::from_inner(scalar_mul_base(k_spend))
+    }
+
+    /// Derive legacy CryptoNote subaddress spend pubkey from its subaddress extension scalar
+    pub fn derive_legacy_subaddress_spend_pubkey(
+        subaddr_extension: &LegacySubaddressExtension,
+        account_spend_pubkey: &AddressSpendPubkey,
+    ) -> Option<Self> {
+        // K^j_s = K_s + m G
+        Some(Self::from_inner(add_edwards(
+            account_spend_pubkey,
+            &scalar_mul_base(subaddr_extension),
+        )?))
+    }
+}
+
+/// Legacy CryptoNote account, whose addresses can receive Carrot enotes
+pub struct LegacyAccount {
+    k_spend: LegacySpendKey,
+    k_view_incoming: ViewIncomingKey,
+    account_spend_pubkey: AddressSpendPubkey,
+    primary_address_view_pubkey: AddressViewPubkey,
+}
+
+impl LegacyAccount {
+    /// Derive a deterministic legacy account, whose view key is derived from the spend key
+    pub fn from_spend_key(k_spend: LegacySpendKey) -> Self {
+        let k_view_incoming = ViewIncomingKey::derive_legacy(&k_spend);
+        Self::new(k_spend, k_view_incoming)
+    }
+
+    /// Bundle a legacy account from independent spend and view keys
+    pub fn new(k_spend: LegacySpendKey, k_view_incoming: ViewIncomingKey) -> Self {
+        // K_s = k_s G
+        let account_spend_pubkey = AddressSpendPubkey::derive_legacy_account_spend_pubkey(&k_spend);
+
+        // K^0_v = k_v G
+        let primary_address_view_pubkey =
+            AddressViewPubkey::derive_primary_address_view_pubkey(&k_view_incoming);
+
+        Self {
+            k_spend,
+            k_view_incoming,
+            account_spend_pubkey,
+            primary_address_view_pubkey,
+        }
+    }
+
+    /// Spend key k_s
+    pub fn spend_key(&self) -> &LegacySpendKey {
+        &self.k_spend
+    }
+
+    /// View key k_v
+    pub fn view_incoming_key(&self) -> &ViewIncomingKey {
+        &self.k_view_incoming
+    }
+
+    /// Account spend pubkey K_s
+    pub fn account_spend_pubkey(&self) -> &AddressSpendPubkey {
+        &self.account_spend_pubkey
+    }
+
+    /// Primary address view pubkey K^0_v
+    pub fn primary_address_view_pubkey(&self) -> &AddressViewPubkey {
+        &self.primary_address_view_pubkey
+    }
+
+    /// Main address (K_s, K^0_v)
+    pub fn main_address(&self) -> CarrotDestinationV1 {
+        CarrotDestinationV1::make_main_address(
+            self.account_spend_pubkey.clone(),
+            self.primary_address_view_pubkey.clone(),
+        )
+    }
+
+    /// Subaddress at index (j_major, j_minor), or the main address at index (0, 0)
+    pub fn subaddress(&self, major_index: u32, minor_index: u32) -> CarrotDestinationV1 {
+        if major_index == 0 && minor_index == 0 {
+            return self.main_address();
+        }
+
+        // m = H_n("SubAddr" || IntToBytes8(0) || k_v || IntToBytes32(j_major) || IntToBytes32(j_minor))
+        let subaddr_extension =
+            LegacySubaddressExtension::derive(&self.k_view_incoming, major_index, minor_index);
+
+        // K^j_s = K_s + m G
+        let address_spend_pubkey = AddressSpendPubkey::derive_legacy_subaddress_spend_pubkey(
+            &subaddr_extension,
+            &self.account_spend_pubkey,
+        )
+        .expect("account spend pubkey is always a valid point");
+
+        // K^j_v = k_v K^j_s
+        let address_view_pubkey = AddressViewPubkey::derive_carrot_account_view_pubkey(
+            &self.k_view_incoming,
+            &address_spend_pubkey,
+        )
+        .expect("subaddress spend pubkey is always a valid point");
+
+        CarrotDestinationV1 {
+            address_spend_pubkey,
+            address_view_pubkey,
+            is_subaddress: true,
+            payment_id: PaymentId::default(),
+        }
+    }
+
+    /// Integrated address (K_s, K^0_v, pid)
+    pub fn integrated_address(&self, payment_id: PaymentId) -> CarrotDestinationV1 {
+        CarrotDestinationV1::make_integrated_address(
+            self.account_spend_pubkey.clone(),
+            self.primary_address_view_pubkey.clone(),
+            payment_id,
+        )
+    }
+
+    /// Opening scalars (k^g_a, k^t_a) of the address spend pubkey at index (j_major, j_minor)
+    pub fn opening_for_subaddress(
+        &self,
+        major_index: u32,
+        minor_index: u32,
+    ) -> (OpeningScalarSecret, OpeningScalarSecret) {
+        // m = 0 for the main address
+        let subaddr_extension =
+            LegacySubaddressExtension::derive(&self.k_view_incoming, major_index, minor_index);
+
+        // k^g_a = k_s + m, k^t_a = 0
+        make_legacy_address_opening(&self.k_spend, &subaddr_extension)
+    }
+
+    /// Opening (x, y) of a one-time address K_o = x G + y T received to index (j_major, j_minor),
+    /// or None if the scanned enote wasn't actually addressed to that index
+    pub fn try_open_onetime_address(
+        &self,
+        major_index: u32,
+        minor_index: u32,
+        sender_extension_g: &OnetimeExtensionG,
+        sender_extension_t: &OnetimeExtensionT,
+        onetime_address: &OutputPubkey,
+    ) -> Option<(OpeningScalarSecret, OpeningScalarSecret)> {
+        let (address_privkey_g, address_privkey_t) =
+            self.opening_for_subaddress(major_index, minor_index);
+
+        // x = k^g_a + k^g_o, y = k^t_a + k^t_o
+        try_open_onetime_address(
+            &address_privkey_g,
+            &address_privkey_t,
+            sender_extension_g,
+            sender_extension_t,
+            onetime_address,
+        )
+    }
+
+    /// Key image L of a one-time address K_o received to index (j_major, j_minor)
+    pub fn key_image(
+        &self,
+        major_index: u32,
+        minor_index: u32,
+        sender_extension_g: &OnetimeExtensionG,
+        onetime_address: &OutputPubkey,
+    ) -> Option<KeyImage> {
+        // m = 0 for the main address
+        let subaddr_extension =
+            LegacySubaddressExtension::derive(&self.k_view_incoming, major_index, minor_index);
+
+        // L = (k_s + m + k^g_o) Hp(Ko)
+        make_legacy_key_image(
+            onetime_address,
+            sender_extension_g,
+            &subaddr_extension,
+            &self.k_spend,
+        )
+        .ok()
+    }
+
+    /// Opening (x, y) of the one-time address of an owned enote record, or None if the record wasn't
+    /// received to a legacy address of this account
+    pub fn try_open_enote_record(
+        &self,
+        record: &CarrotOwnedEnoteRecordV1,
+    ) -> Option<(OpeningScalarSecret, OpeningScalarSecret)> {
+        let subaddr_index = record.subaddress_index();
+        if subaddr_index.derive_type != AddressDeriveType::Legacy {
+            return None;
+        }
+        self.try_open_onetime_address(
+            subaddr_index.index.major,
+            subaddr_index.index.minor,
+            record.sender_extension_g(),
+            record.sender_extension_t(),
+            record.onetime_address(),
+        )
+    }
+
+    /// Key image L of the one-time address of an owned enote record, or None if the record wasn't
+    /// received to a legacy address of this account
+    pub fn enote_record_key_image(&self, record: &CarrotOwnedEnoteRecordV1) -> Option<KeyImage> {
+        let subaddr_index = record.subaddress_index();
+        if subaddr_index.derive_type != AddressDeriveType::Legacy {
+            return None;
+        }
+        self.key_image(
+            subaddr_index.index.major,
+            subaddr_index.index.minor,
+            record.sender_extension_g(),
+            record.onetime_address(),
+        )
+    }
+}
+
+impl Random for LegacyAccount {
+    type Params = ();
+    fn new_random_with_params<R: rand_core::CryptoRngCore + ?Sized>(
+        rng: &mut R,
+        _: Self::Params,
+    ) -> Self {
+        Self::from_spend_key(LegacySpendKey::new_random_with_params(rng, ()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::legacy::*;
+    use crate::opening::OpenedPoint;
+    use crate::unit_testing::*;
+
+    #[test]
+    fn converge_make_legacy_view_key() {
+        assert_eq_hex!(
+            "fadf3558b700b88936113be1e5342245bd68a6b1deeb496000c4148ad4b61f02",
+            ViewIncomingKey::derive_legacy(&hex_into!(
+                "c595161ea20ccd8c692947c2d3ced471e9b13a18b150c881232794e8042bf107"
+            ))
+        );
+    }
+
+    #[test]
+    fn legacy_subaddress_opening() {
+        let account = LegacyAccount::new_random_with_params(&mut rand_core::OsRng, ());
+        for (major_index, minor_index) in [(0, 0), (0, 1), (1, 0), (7, 3)] {
+            let (x, y) = account.opening_for_subaddress(major_index, minor_index);
+            let address = account.subaddress(major_index, minor_index);
+            assert_eq!(
+                AddressSpendPubkey::from(OpenedPoint::scalar_mul_gt(&x, &y)),
+                address.address_spend_pubkey
+            );
+            assert_eq!(major_index != 0 || minor_index != 0, address.is_subaddress);
+        }
+    }
+}
@@ -1,29 +1,56 @@
 #![no_std]
 //#![deny(missing_docs)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 pub mod account;
+pub mod address;
 mod as_crypto;
+mod base58;
+pub mod commitments;
 mod consts;
 mod destination;
 pub mod device;
 mod domain_separators;
 mod enote;
 mod enote_components;
+#[cfg(feature = "alloc")]
+pub mod enote_store;
+pub mod fees;
 mod hash_functions;
 mod impls;
+#[cfg(feature = "alloc")]
+pub mod input_selection;
+pub mod legacy;
 mod math_utils;
+pub mod message_signing;
 pub mod opening;
-mod output_set_finalization;
+pub mod output_set_finalization;
 pub mod payments;
 mod permutate;
 pub mod random;
+#[cfg(feature = "alloc")]
+pub mod reserve_proofs;
 pub mod scan;
+#[cfg(feature = "alloc")]
+pub mod scan_batch;
+#[cfg(feature = "alloc")]
+pub mod scan_tx;
 mod scan_unsafe;
+#[cfg(feature = "alloc")]
+pub mod subaddress_table;
 mod transcript;
+pub mod tx_proofs;
+#[cfg(feature = "alloc")]
+pub mod tx_builder;
 mod type_macros;
+#[cfg(feature = "alloc")]
+pub mod uri;
 #[cfg(test)]
 mod unit_testing;
 
 pub use account::*;
 pub use destination::*;
+pub use enote::*;
 pub use enote_components::*;
@@ -1,7 +1,7 @@
 use curve25519_dalek::{EdwardsPoint, MontgomeryPoint, edwards::CompressedEdwardsY};
 use group::GroupEncoding;
 
-use crate::as_crypto::{AsEdwardsPoint, AsScalar};
+use crate::as_crypto::{AsEdwardsPoint, AsMontgomeryPoint, AsScalar};
 
 pub fn scalar_mul_t<S>(y: &S) -> CompressedEdwardsY
 where
@@ -56,6 +56,21 @@ where
     Some((A - B).compress())
 }
 
+#[allow(non_snake_case)]
+pub fn scalar_mul_montgomery_via_edwards<S, M>(a: &S, D: &M) -> MontgomeryPoint
+where
+    S: AsScalar,
+    M: AsMontgomeryPoint,
+{
+    // a D, where u(a P) = u(a (-P)), so the sign of the Edwards point doesn't matter. points which
+    // don't map onto the Edwards curve (i.e. on the twist) fall back to the Montgomery ladder
+    let D = D.as_montgomery_ref();
+    match D.to_edwards(0) {
+        Some(P) => (a.as_scalar_ref() * P).to_montgomery(),
+        None => a.as_scalar_ref() * D,
+    }
+}
+
 pub fn scalar_mul_base_montgomery<S>(a: &S) -> MontgomeryPoint
 where
     S: AsScalar,
@@ -63,6 +78,25 @@ where
     MontgomeryPoint::mul_base(a.as_scalar_ref())
 }
 
+#[allow(non_snake_case)]
+pub fn hash_to_point<E>(P: &E) -> EdwardsPoint
+where
+    E: AsEdwardsPoint,
+{
+    // Hp(P)
+    monero_generators::biased_hash_to_point(P.as_edwards_ref().0)
+}
+
+#[allow(non_snake_case)]
+pub fn scalar_mul_hash_to_point<S, E>(a: &S, P: &E) -> CompressedEdwardsY
+where
+    S: AsScalar,
+    E: AsEdwardsPoint,
+{
+    // a Hp(P)
+    (a.as_scalar_ref() * hash_to_point(P)).compress()
+}
+
 pub fn scalar_mul_base<S>(a: &S) -> CompressedEdwardsY
 where
     S: AsScalar,
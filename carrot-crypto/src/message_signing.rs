@@ -0,0 +1,110 @@
+use curve25519_dalek::{EdwardsPoint, Scalar, edwards::CompressedEdwardsY};
+use group::GroupEncoding;
+
+use crate::as_crypto::{AsEdwardsPoint, AsScalar};
+use crate::domain_separators;
+use crate::hash_functions::*;
+use crate::random::new_random;
+use crate::transcript::*;
+use crate::*;
+
+#[allow(non_snake_case)]
+fn make_message_signature_challenge(
+    destination: &CarrotDestinationV1,
+    message: &[u8],
+    R: &CompressedEdwardsY,
+) -> Scalar {
+    // c = H_n[H_32(msg)](K^j_s, K^j_v, R)
+    let message_hash = derive_bytes_32(message, &[]);
+    let transcript = make_carrot_transcript!(domain_separators::MESSAGE_SIGNATURE_CHALLENGE,
+        AddressSpendPubkey : &destination.address_spend_pubkey,
+        AddressViewPubkey : &destination.address_view_pubkey, CompressedEdwardsY : R);
+    derive_scalar(&transcript, &message_hash)
+}
+
+/// Signature of an arbitrary message by the owner of an address: a Schnorr proof of knowledge of
+/// the opening (k^g_a, k^t_a) of the address spend pubkey K^j_s = k^g_a G + k^t_a T
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CarrotMessageSignatureV1 {
+    /// c
+    pub challenge: Scalar,
+    /// r_g
+    pub response_g: Scalar,
+    /// r_t
+    pub response_t: Scalar,
+}
+
+impl CarrotMessageSignatureV1 {
+    /// Sign a message with the address at index (j_major, j_minor) of an account, or None if the
+    /// subaddress can't be derived
+    #[allow(non_snake_case)]
+    pub fn sign<R>(
+        account: &CarrotAccount,
+        major_index: u32,
+        minor_index: u32,
+        message: &[u8],
+        rng: &mut R,
+    ) -> Option<Self>
+    where
+        R: rand_core::CryptoRngCore + ?Sized,
+    {
+        let destination = account.subaddress(major_index, minor_index)?;
+
+        // k^g_a = k_gi * k^j_subscal, k^t_a = k_ps * k^j_subscal
+        let (address_privkey_g, address_privkey_t) =
+            account.opening_for_subaddress(major_index, minor_index)?;
+
+        // R = k_g G + k_t T
+        let k_g: Scalar = new_random(rng);
+        let k_t: Scalar = new_random(rng);
+        let R = (EdwardsPoint::mul_base(&k_g) + k_t * *monero_generators::T).compress();
+
+        // r_g = k_g - c k^g_a, r_t = k_t - c k^t_a
+        let challenge = make_message_signature_challenge(&destination, message, &R);
+        Some(Self {
+            challenge,
+            response_g: k_g - challenge * address_privkey_g.as_scalar_ref(),
+            response_t: k_t - challenge * address_privkey_t.as_scalar_ref(),
+        })
+    }
+
+    /// Verify a message signature by the owner of a destination address. The payment ID isn't
+    /// signed, so a signature with the main address also verifies with its integrated addresses.
+    #[allow(non_snake_case)]
+    pub fn verify(&self, destination: &CarrotDestinationV1, message: &[u8]) -> bool {
+        let Some(K_s) =
+            EdwardsPoint::from_bytes(&destination.address_spend_pubkey.as_edwards_ref().0)
+                .into_option()
+                .filter(|K_s| K_s.is_torsion_free())
+        else {
+            return false;
+        };
+
+        // R = r_g G + r_t T + c K^j_s
+        let R = EdwardsPoint::mul_base(&self.response_g)
+            + self.response_t * *monero_generators::T
+            + self.challenge * K_s;
+        self.challenge == make_message_signature_challenge(destination, message, &R.compress())
+    }
+
+    /// Serialize as c || r_g || r_t
+    pub fn to_bytes(&self) -> [u8; 96] {
+        let mut bytes = [0u8; 96];
+        bytes[..32].copy_from_slice(self.challenge.as_bytes());
+        bytes[32..64].copy_from_slice(self.response_g.as_bytes());
+        bytes[64..].copy_from_slice(self.response_t.as_bytes());
+        bytes
+    }
+
+    /// Deserialize from c || r_g || r_t, or None if a scalar isn't canonical
+    pub fn from_bytes(bytes: &[u8; 96]) -> Option<Self> {
+        let scalar_from_slice = |slice: &[u8]| {
+            Scalar::from_canonical_bytes(slice.try_into().expect("32-byte slice")).into_option()
+        };
+        Some(Self {
+            challenge: scalar_from_slice(&bytes[..32])?,
+            response_g: scalar_from_slice(&bytes[32..64])?,
+            response_t: scalar_from_slice(&bytes[64..])?,
+        })
+    }
+}
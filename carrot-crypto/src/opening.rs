@@ -33,6 +33,56 @@ impl AsEdwardsPoint for OpenedPoint {
     }
 }
 
+/// Opening (k^g_a, k^t_a) of a Carrot-derived address spend pubkey K^j_s = k^g_a G + k^t_a T
+pub fn make_carrot_address_opening(
+    k_prove_spend: &crate::ProveSpendKey,
+    k_generate_image: &crate::GenerateImageKey,
+    subaddr_scalar: &crate::SubaddressScalarSecret,
+) -> (OpeningScalarSecret, OpeningScalarSecret) {
+    // k^g_a = k_gi * k^j_subscal
+    let address_privkey_g = k_generate_image * subaddr_scalar;
+
+    // k^t_a = k_ps * k^j_subscal
+    let address_privkey_t = k_prove_spend * subaddr_scalar;
+
+    (address_privkey_g, address_privkey_t)
+}
+
+/// Opening (k^g_a, k^t_a) of a legacy address spend pubkey K^j_s = k^g_a G + k^t_a T
+pub fn make_legacy_address_opening(
+    k_spend: &crate::legacy::LegacySpendKey,
+    subaddr_extension: &crate::legacy::LegacySubaddressExtension,
+) -> (OpeningScalarSecret, OpeningScalarSecret) {
+    // k^g_a = k_s + m
+    let address_privkey_g = k_spend + subaddr_extension;
+
+    // k^t_a = 0
+    (address_privkey_g, OpeningScalarSecret::default())
+}
+
+/// Opening (x, y) of a one-time address K_o = x G + y T, given the opening of its address spend
+/// pubkey and the sender extensions, or None if the opening doesn't match the one-time address
+pub fn try_open_onetime_address(
+    address_privkey_g: &OpeningScalarSecret,
+    address_privkey_t: &OpeningScalarSecret,
+    sender_extension_g: &crate::OnetimeExtensionG,
+    sender_extension_t: &crate::OnetimeExtensionT,
+    onetime_address: &crate::OutputPubkey,
+) -> Option<(OpeningScalarSecret, OpeningScalarSecret)> {
+    // x = k^g_a + k^g_o
+    let x = address_privkey_g + sender_extension_g;
+
+    // y = k^t_a + k^t_o
+    let y = address_privkey_t + sender_extension_t;
+
+    // x G + y T ?= K_o
+    if OpenedPoint::scalar_mul_gt(&x, &y).as_edwards_ref() != onetime_address.as_edwards_ref() {
+        return None;
+    }
+
+    Some((x, y))
+}
+
 macro_rules! impl_opening_add {
     (ncomm ; $lhs:path, $rhs:path) => {
         impl core::ops::Add<$rhs> for $lhs {
@@ -118,6 +168,7 @@ macro_rules! impl_from_point {
 impl_opening_add! {crate::ProveSpendKey, OpeningScalarSecret}
 impl_opening_add! {crate::OnetimeExtensionG, OpeningScalarSecret}
 impl_opening_add! {crate::OnetimeExtensionT, OpeningScalarSecret}
+impl_opening_add! {crate::legacy::LegacySpendKey, crate::legacy::LegacySubaddressExtension}
 
 impl_opening_mul! {crate::ProveSpendKey, crate::SubaddressScalarSecret}
 impl_opening_mul! {crate::GenerateImageKey, crate::SubaddressScalarSecret}
@@ -1,3 +1,5 @@
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
 use core::ops::{Index, IndexMut};
 
 use crate::as_crypto::AsEdwardsPoint;
@@ -13,6 +15,7 @@ use crate::random::Random;
 use crate::random::new_random;
 use crate::*;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum AdditionalOutputType {
     PaymentShared, // self-send proposal with enote_type="payment" with a shared D_e
     ChangeShared,  // self-send proposal with enote_type="change" with a shared D_e
@@ -101,7 +104,7 @@ where
                 destination_address_spend_pubkey: change_address_spend_pubkey.clone(),
                 amount: needed_change_amount,
                 enote_type: CarrotEnoteType::Change,
-                enote_ephemeral_pubkey: None,
+                enote_ephemeral_pubkey: Some(new_random(rng)),
                 internal_message: None,
             })
         }
@@ -270,26 +273,21 @@ where
         };
     }
 
+    // temporarily index self-send proposals by output index, so the order is a permutation
+    for (is_selfsend, idx) in payment_proposal_order_out.iter_mut() {
+        if *is_selfsend {
+            *idx += normal_payment_proposals.len();
+        }
+    }
+
     // sort enotes by K_o
     payment_proposal_order_out.sort_by(|a, b| {
-        let a_output_idx = a.1
-            + if a.0 {
-                normal_payment_proposals.len()
-            } else {
-                0
-            };
-        let b_output_idx = b.1
-            + if b.0 {
-                normal_payment_proposals.len()
-            } else {
-                0
-            };
-        let a_pk_bytes = &output_enote_proposals_out[a_output_idx]
+        let a_pk_bytes = &output_enote_proposals_out[a.1]
             .enote
             .onetime_address
             .as_edwards_ref()
             .0;
-        let b_pk_bytes = &output_enote_proposals_out[b_output_idx]
+        let b_pk_bytes = &output_enote_proposals_out[b.1]
             .enote
             .onetime_address
             .as_edwards_ref()
@@ -303,6 +301,13 @@ where
         output_enote_proposals_out,
     );
 
+    // index self-send proposals by their position in selfsend_payment_proposals again
+    for (is_selfsend, idx) in payment_proposal_order_out.iter_mut() {
+        if *is_selfsend {
+            *idx -= normal_payment_proposals.len();
+        }
+    }
+
     // assert uniqueness of D_e if >2-out, shared otherwise. also check D_e is not trivial
     let mut has_unique_ephemeral_pubkeys = true;
     for i in 0..output_enote_proposals_out.len() {
@@ -416,6 +421,9 @@ pub fn get_coinbase_output_enotes(
         a_pk_bytes.cmp(&b_pk_bytes)
     });
 
+    // reorder output_coinbase_enotes_out according to payment_proposal_order_out
+    apply_permutation_backwards(payment_proposal_order_out, output_coinbase_enotes_out);
+
     // assert a) uniqueness of K_o, b) all K_o lie in prime order subgroup, and c) K_o is sorted
     for i in 0..output_coinbase_enotes_out.len() {
         let i_out_pk = &output_coinbase_enotes_out[i].onetime_address;
@@ -431,3 +439,122 @@ pub fn get_coinbase_output_enotes(
 
     Ok(())
 }
+
+/// Payment proposal which an output enote was made from, by its index in its proposal list
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaymentProposalOrder {
+    /// index into the normal payment proposals
+    Normal(usize),
+    /// index into the self-send payment proposals
+    SelfSend(usize),
+}
+
+impl From<(bool, usize)> for PaymentProposalOrder {
+    fn from((is_selfsend, idx): (bool, usize)) -> Self {
+        if is_selfsend {
+            Self::SelfSend(idx)
+        } else {
+            Self::Normal(idx)
+        }
+    }
+}
+
+/// Same as [`get_output_enote_proposals`], but allocates and returns the output enote proposals,
+/// sorted by K_o, pid_enc, and the payment proposal each output enote proposal was made from
+#[cfg(feature = "alloc")]
+pub fn get_output_enote_proposals_vec<VB, VI>(
+    normal_payment_proposals: &[CarrotPaymentProposalV1],
+    selfsend_payment_proposals: &[CarrotPaymentProposalSelfSendV1],
+    dummy_encrypted_payment_id: &Option<EncryptedPaymentId>,
+    s_view_balance_dev: Option<&VB>,
+    k_view_dev: Option<&VI>,
+    tx_first_key_image: KeyImage,
+) -> Result<(
+    Vec<RCTOutputEnoteProposal>,
+    EncryptedPaymentId,
+    Vec<PaymentProposalOrder>,
+)>
+where
+    VB: ViewBalanceSecretDevice,
+    VI: ViewIncomingKeyDevice,
+{
+    let num_proposals = normal_payment_proposals.len() + selfsend_payment_proposals.len();
+    let mut output_enote_proposals = Vec::with_capacity(num_proposals);
+    output_enote_proposals.resize_with(num_proposals, empty_output_enote_proposal);
+    let mut encrypted_payment_id = EncryptedPaymentId::default();
+    let mut payment_proposal_order = vec![(false, 0); num_proposals];
+
+    get_output_enote_proposals(
+        normal_payment_proposals,
+        selfsend_payment_proposals,
+        dummy_encrypted_payment_id,
+        s_view_balance_dev,
+        k_view_dev,
+        tx_first_key_image,
+        &mut output_enote_proposals,
+        &mut encrypted_payment_id,
+        &mut payment_proposal_order,
+    )?;
+
+    Ok((
+        output_enote_proposals,
+        encrypted_payment_id,
+        payment_proposal_order
+            .into_iter()
+            .map(PaymentProposalOrder::from)
+            .collect(),
+    ))
+}
+
+/// Same as [`get_coinbase_output_enotes`], but allocates and returns the coinbase enotes, sorted
+/// by K_o, and the index of the payment proposal each enote was made from
+#[cfg(feature = "alloc")]
+pub fn get_coinbase_output_enotes_vec(
+    normal_payment_proposals: &[CarrotPaymentProposalV1],
+    block_index: BlockIndex,
+) -> Result<(Vec<CarrotCoinbaseEnoteV1>, Vec<usize>)> {
+    let num_proposals = normal_payment_proposals.len();
+    let mut output_coinbase_enotes = Vec::with_capacity(num_proposals);
+    output_coinbase_enotes.resize_with(num_proposals, || empty_coinbase_enote(block_index));
+    let mut payment_proposal_order = vec![0; num_proposals];
+
+    get_coinbase_output_enotes(
+        normal_payment_proposals,
+        block_index,
+        &mut output_coinbase_enotes,
+        &mut payment_proposal_order,
+    )?;
+
+    Ok((output_coinbase_enotes, payment_proposal_order))
+}
+
+// placeholder to be overwritten by get_output_enote_proposals()
+#[cfg(feature = "alloc")]
+fn empty_output_enote_proposal() -> RCTOutputEnoteProposal {
+    RCTOutputEnoteProposal {
+        enote: CarrotEnoteV1 {
+            onetime_address: Default::default(),
+            amount_commitment: Default::default(),
+            amount_enc: Default::default(),
+            anchor_enc: Default::default(),
+            view_tag: Default::default(),
+            enote_ephemeral_pubkey: Default::default(),
+            tx_first_key_image: Default::default(),
+        },
+        amount: 0,
+        amount_blinding_factor: Default::default(),
+    }
+}
+
+// placeholder to be overwritten by get_coinbase_output_enotes()
+#[cfg(feature = "alloc")]
+fn empty_coinbase_enote(block_index: BlockIndex) -> CarrotCoinbaseEnoteV1 {
+    CarrotCoinbaseEnoteV1 {
+        onetime_address: Default::default(),
+        amount: 0,
+        anchor_enc: Default::default(),
+        view_tag: Default::default(),
+        enote_ephemeral_pubkey: Default::default(),
+        block_index,
+    }
+}
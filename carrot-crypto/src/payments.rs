@@ -48,12 +48,16 @@ pub struct RCTOutputEnoteProposal {
 }
 
 /// Type of error encountered finalizing payments
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ErrorKind {
+    /// Sum of amounts or weights doesn't fit in a u64
+    AmountOverflow,
     /// Address contains invalid/torsioned elliptic curve points
     BadAddressPoints,
     /// Device threw an error
     DeviceError,
+    /// Inputs don't cover the payment amounts and fee
+    InsufficientFunds,
     /// Internal message is invalid for given type of payment
     InvalidInternalMessage,
     /// Conflicting enote ephemeral pubkeys provided for a self-send payment
@@ -64,6 +68,8 @@ pub enum ErrorKind {
     MissingPaymentId,
     /// Missing randomness/anchor_norm for normal payment
     MissingRandomness,
+    /// Input amounts don't equal output amounts plus the fee
+    UnbalancedAmounts,
     /// Address type cannot be used in these payment set
     WrongAddressType,
     /// Bad number of outputs in a set (too low)
@@ -86,6 +92,10 @@ impl Error {
     pub fn new(kind: ErrorKind) -> Self {
         Self { kind }
     }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
 }
 
 impl core::error::Error for Error {}
@@ -42,7 +42,7 @@ fn idx_is_marked(idx: usize) -> bool {
 
 pub(crate) fn apply_permutation_backwards<P, T>(permutation: &mut P, data: &mut [T])
 where
-    P: IndexMut<usize, Output = usize>,
+    P: IndexMut<usize, Output = usize> + ?Sized,
 {
     assert!(data.len() <= isize::max_value() as usize);
 
@@ -0,0 +1,224 @@
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+
+use curve25519_dalek::{EdwardsPoint, Scalar, edwards::CompressedEdwardsY};
+
+use crate::as_crypto::{AsEdwardsPoint, AsScalar};
+use crate::domain_separators;
+use crate::hash_functions::*;
+use crate::legacy::LegacyAccount;
+use crate::math_utils::hash_to_point;
+use crate::opening::OpeningScalarSecret;
+use crate::random::new_random;
+use crate::transcript::*;
+use crate::tx_proofs::{Error, ErrorKind, Result, decompress_torsion_free};
+use crate::*;
+
+/// Account which can open the one-time addresses of enotes received to its addresses, and make
+/// their key images
+pub trait OnetimeAddressOpener {
+    /// Opening (x, y) of the one-time address K_o = x G + y T of an owned enote record
+    fn try_open_enote_record(
+        &self,
+        record: &CarrotOwnedEnoteRecordV1,
+    ) -> Option<(OpeningScalarSecret, OpeningScalarSecret)>;
+
+    /// Key image L = x Hp(K_o) of the one-time address of an owned enote record
+    fn enote_record_key_image(&self, record: &CarrotOwnedEnoteRecordV1) -> Option<KeyImage>;
+}
+
+macro_rules! impl_onetime_address_opener_for_account {
+    ($account_type:ty) => {
+        impl OnetimeAddressOpener for $account_type {
+            fn try_open_enote_record(
+                &self,
+                record: &CarrotOwnedEnoteRecordV1,
+            ) -> Option<(OpeningScalarSecret, OpeningScalarSecret)> {
+                <$account_type>::try_open_enote_record(self, record)
+            }
+
+            fn enote_record_key_image(
+                &self,
+                record: &CarrotOwnedEnoteRecordV1,
+            ) -> Option<KeyImage> {
+                <$account_type>::enote_record_key_image(self, record)
+            }
+        }
+    };
+}
+
+impl_onetime_address_opener_for_account!(CarrotAccount);
+impl_onetime_address_opener_for_account!(LegacyAccount);
+
+/// Proof of ownership of a single enote: a proof of knowledge of the opening (x, y) of
+/// K_o = x G + y T, where x is also the discrete log of the key image L to the base Hp(K_o)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CarrotReserveProofEntryV1 {
+    /// K_o
+    pub onetime_address: OutputPubkey,
+    /// C_a
+    pub amount_commitment: AmountCommitment,
+    /// a
+    pub amount: Amount,
+    /// k_a
+    pub amount_blinding_factor: AmountBlindingKey,
+    /// L
+    pub key_image: KeyImage,
+    /// c
+    pub challenge: Scalar,
+    /// r_x
+    pub response_g: Scalar,
+    /// r_y
+    pub response_t: Scalar,
+}
+
+#[allow(non_snake_case)]
+fn make_reserve_proof_challenge(
+    message_hash: &[u8; 32],
+    onetime_address: &OutputPubkey,
+    amount_commitment: &AmountCommitment,
+    key_image: &KeyImage,
+    R_o: &CompressedEdwardsY,
+    R_L: &CompressedEdwardsY,
+) -> Scalar {
+    // c = H_n[H_32(msg)](K_o, C_a, L, R_o, R_L)
+    let transcript = make_carrot_transcript!(domain_separators::RESERVE_PROOF_CHALLENGE,
+        OutputPubkey : onetime_address, AmountCommitment : amount_commitment, KeyImage : key_image,
+        CompressedEdwardsY : R_o, CompressedEdwardsY : R_L);
+    derive_scalar(&transcript, message_hash)
+}
+
+impl CarrotReserveProofEntryV1 {
+    #[allow(non_snake_case)]
+    fn prove<A, R>(
+        account: &A,
+        record: &CarrotOwnedEnoteRecordV1,
+        message_hash: &[u8; 32],
+        rng: &mut R,
+    ) -> Result<Self>
+    where
+        A: OnetimeAddressOpener,
+        R: rand_core::CryptoRngCore + ?Sized,
+    {
+        let onetime_address = record.onetime_address();
+        let (x, y) = account
+            .try_open_enote_record(record)
+            .ok_or(Error::new(ErrorKind::OpeningFailed))?;
+        let key_image = account
+            .enote_record_key_image(record)
+            .ok_or(Error::new(ErrorKind::OpeningFailed))?;
+
+        // R_o = k_x G + k_y T, R_L = k_x Hp(K_o)
+        let k_x: Scalar = new_random(rng);
+        let k_y: Scalar = new_random(rng);
+        let R_o = (EdwardsPoint::mul_base(&k_x) + k_y * *monero_generators::T).compress();
+        let R_L = (k_x * hash_to_point(onetime_address)).compress();
+
+        // r_x = k_x - c x, r_y = k_y - c y
+        let amount_commitment = record.amount_commitment();
+        let challenge = make_reserve_proof_challenge(
+            message_hash,
+            onetime_address,
+            &amount_commitment,
+            &key_image,
+            &R_o,
+            &R_L,
+        );
+        Ok(Self {
+            onetime_address: onetime_address.clone(),
+            amount_commitment,
+            amount: record.amount(),
+            amount_blinding_factor: record.amount_blinding_factor(),
+            key_image,
+            challenge,
+            response_g: k_x - challenge * x.as_scalar_ref(),
+            response_t: k_y - challenge * y.as_scalar_ref(),
+        })
+    }
+
+    #[allow(non_snake_case)]
+    fn verify(&self, message_hash: &[u8; 32]) -> Result<()> {
+        // C_a = k_a G + a H
+        if self.amount_commitment
+            != AmountCommitment::commit(self.amount, &self.amount_blinding_factor)
+        {
+            return Err(Error::new(ErrorKind::AmountCommitmentMismatch));
+        }
+
+        let K_o = decompress_torsion_free(&self.onetime_address)?;
+        let L = decompress_torsion_free(&self.key_image)?;
+
+        // R_o = r_x G + r_y T + c K_o, R_L = r_x Hp(K_o) + c L
+        let R_o = EdwardsPoint::mul_base(&self.response_g)
+            + self.response_t * *monero_generators::T
+            + self.challenge * K_o;
+        let R_L = self.response_g * hash_to_point(&self.onetime_address) + self.challenge * L;
+        let challenge = make_reserve_proof_challenge(
+            message_hash,
+            &self.onetime_address,
+            &self.amount_commitment,
+            &self.key_image,
+            &R_o.compress(),
+            &R_L.compress(),
+        );
+        if challenge != self.challenge {
+            return Err(Error::new(ErrorKind::InvalidProof));
+        }
+        Ok(())
+    }
+}
+
+/// Reserve proof: proves ownership of a set of enotes, revealing their amounts and key images.
+/// Verification needs no wallet secrets, but the verifier must still check against the chain that
+/// each (K_o, C_a) is an enote, and that none of the key images have been spent.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CarrotReserveProofV1 {
+    /// one proof of ownership per enote
+    pub entries: Vec<CarrotReserveProofEntryV1>,
+}
+
+impl CarrotReserveProofV1 {
+    /// Prove ownership of owned enotes, binding the proof to a message
+    pub fn prove<A, R>(
+        account: &A,
+        records: &[CarrotOwnedEnoteRecordV1],
+        message: &[u8],
+        rng: &mut R,
+    ) -> Result<Self>
+    where
+        A: OnetimeAddressOpener,
+        R: rand_core::CryptoRngCore + ?Sized,
+    {
+        let message_hash = derive_bytes_32(message, &[]);
+        let entries = records
+            .iter()
+            .map(|record| CarrotReserveProofEntryV1::prove(account, record, &message_hash, rng))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { entries })
+    }
+
+    /// Verify the proofs of ownership, and return the total proven amount
+    pub fn verify(&self, message: &[u8]) -> Result<Amount> {
+        let message_hash = derive_bytes_32(message, &[]);
+        let mut onetime_addresses = BTreeSet::new();
+        let mut key_images = BTreeSet::new();
+        let mut total_amount: Amount = 0;
+        for entry in self.entries.iter() {
+            if !onetime_addresses.insert(entry.onetime_address.as_edwards_ref().to_bytes())
+                || !key_images.insert(entry.key_image.as_edwards_ref().to_bytes())
+            {
+                return Err(Error::new(ErrorKind::DuplicateEnote));
+            }
+            entry.verify(&message_hash)?;
+            total_amount = total_amount
+                .checked_add(entry.amount)
+                .ok_or(Error::new(ErrorKind::AmountOverflow))?;
+        }
+        Ok(total_amount)
+    }
+
+    /// Key images of the proven enotes, which must all be unspent for the proof to be meaningful
+    pub fn key_images(&self) -> impl Iterator<Item = &KeyImage> {
+        self.entries.iter().map(|entry| &entry.key_image)
+    }
+}
@@ -1,10 +1,72 @@
+use core::fmt::{Debug, Display};
+
 use crate::destination::CarrotDestinationV1;
+#[cfg(feature = "alloc")]
+use crate::device::GenerateAddressSecretDevice;
 use crate::device::ViewIncomingKeyDevice;
-use crate::device::{self, ViewBalanceSecretDevice};
+use crate::device::{self, GenerateImageKeyDevice, ViewBalanceSecretDevice};
 use crate::enote::*;
+use crate::legacy::LegacySubaddressExtension;
 use crate::scan_unsafe::*;
+#[cfg(feature = "alloc")]
+use crate::subaddress_table::SubaddressTable;
 use crate::*;
 
+/// Type of error encountered scanning enotes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Recovered address spend pubkey doesn't belong to the expected destination(s)
+    AddressSpendPubkeyMismatch,
+    /// Amount commitment couldn't be recomputed from the decrypted amount
+    AmountCommitmentMismatch,
+    /// Device threw an error
+    DeviceError,
+    /// Enote or destination contains invalid elliptic curve points
+    InvalidPoint,
+    /// Enote is addressed to us, but failed Janus protection: this is likely an attempted Janus
+    /// attack, and the user should be warned about it
+    JanusProtectionFailed,
+    /// Recovered payment ID doesn't match the destination's payment ID
+    PaymentIdMismatch,
+    /// View tag doesn't match, which is normal for enotes not addressed to us
+    ViewTagMismatch,
+    /// Enote type cannot be used for this kind of enote
+    WrongEnoteType,
+}
+
+/// Error generated while scanning an enote
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+}
+
+/// @TODO: real display
+impl Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(&self.kind, f)
+    }
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind) -> Self {
+        Self { kind }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+impl From<device::Error> for Error {
+    fn from(_: device::Error) -> Self {
+        Self::new(ErrorKind::DeviceError)
+    }
+}
+
+impl core::error::Error for Error {}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
 fn is_main_address_spend_pubkey(
     address_spend_pubkey: &AddressSpendPubkey,
     main_address_spend_pubkeys: &[AddressSpendPubkey],
@@ -21,7 +83,7 @@ fn try_scan_carrot_coinbase_enote_checked(
     enote: &CarrotCoinbaseEnoteV1,
     s_sender_receiver_unctx: &MontgomeryECDH,
     main_address_spend_pubkeys: &[AddressSpendPubkey],
-) -> Option<(OnetimeExtensionG, OnetimeExtensionT, AddressSpendPubkey)> {
+) -> Result<(OnetimeExtensionG, OnetimeExtensionT, AddressSpendPubkey)> {
     // s^ctx_sr, k^g_o, k^g_t, K^j_s, pid, anchor
     let (sender_extension_g, sender_extension_t, address_spend_pubkey, nominal_janus_anchor) = unsafe {
         try_scan_carrot_coinbase_enote_no_janus(
@@ -32,7 +94,7 @@ fn try_scan_carrot_coinbase_enote_checked(
     }?;
 
     if !is_main_address_spend_pubkey(&address_spend_pubkey, main_address_spend_pubkeys) {
-        return None;
+        return Err(Error::new(ErrorKind::AddressSpendPubkeyMismatch));
     }
 
     if !verify_carrot_normal_janus_protection(
@@ -43,10 +105,10 @@ fn try_scan_carrot_coinbase_enote_checked(
         &Default::default(),
         &enote.enote_ephemeral_pubkey,
     ) {
-        return None;
+        return Err(Error::new(ErrorKind::JanusProtectionFailed));
     }
 
-    Some((sender_extension_g, sender_extension_t, address_spend_pubkey))
+    Ok((sender_extension_g, sender_extension_t, address_spend_pubkey))
 }
 
 fn try_scan_carrot_enote_external_normal_checked(
@@ -54,7 +116,7 @@ fn try_scan_carrot_enote_external_normal_checked(
     encrypted_payment_id: Option<&EncryptedPaymentId>,
     s_sender_receiver_unctx: &MontgomeryECDH,
     main_address_spend_pubkeys: &[AddressSpendPubkey],
-) -> Option<(
+) -> Result<(
     OnetimeExtensionG,
     OnetimeExtensionT,
     AddressSpendPubkey,
@@ -93,7 +155,7 @@ fn try_scan_carrot_enote_external_normal_checked(
         )
     };
 
-    Some((
+    Ok((
         sender_extension_g,
         sender_extension_t,
         address_spend_pubkey,
@@ -113,11 +175,51 @@ pub fn make_carrot_uncontextualized_shared_key_receiver<VI: ViewIncomingKeyDevic
     k_view_dev.view_key_scalar_mult_x25519(enote_ephemeral_pubkey)
 }
 
+pub fn make_carrot_key_image<GI: GenerateImageKeyDevice>(
+    onetime_address: &OutputPubkey,
+    sender_extension_g: &OnetimeExtensionG,
+    subaddr_scalar: &SubaddressScalarSecret,
+    k_generate_image_dev: &GI,
+) -> Result<KeyImage> {
+    // L_partial = k_gi Hp(Ko)
+    let partial_key_image =
+        k_generate_image_dev.generate_image_scalar_mult_hash_to_point(onetime_address)?;
+
+    // L = (k^j_subscal k_gi + k^g_o) Hp(Ko)
+    KeyImage::derive_carrot(
+        &partial_key_image,
+        subaddr_scalar,
+        sender_extension_g,
+        onetime_address,
+    )
+    .ok_or(Error::new(ErrorKind::InvalidPoint))
+}
+
+pub fn make_legacy_key_image<GI: GenerateImageKeyDevice>(
+    onetime_address: &OutputPubkey,
+    sender_extension_g: &OnetimeExtensionG,
+    subaddr_extension: &LegacySubaddressExtension,
+    k_spend_dev: &GI,
+) -> Result<KeyImage> {
+    // L_partial = k_s Hp(Ko)
+    let partial_key_image =
+        k_spend_dev.generate_image_scalar_mult_hash_to_point(onetime_address)?;
+
+    // L = (k_s + m + k^g_o) Hp(Ko)
+    KeyImage::derive_legacy(
+        &partial_key_image,
+        subaddr_extension,
+        sender_extension_g,
+        onetime_address,
+    )
+    .ok_or(Error::new(ErrorKind::InvalidPoint))
+}
+
 pub fn try_scan_carrot_coinbase_enote_sender_with_anchor_norm(
     enote: &CarrotCoinbaseEnoteV1,
     destination: &CarrotDestinationV1,
     anchor_norm: &JanusAnchor,
-) -> Option<(OnetimeExtensionG, OnetimeExtensionT)> {
+) -> Result<CarrotCoinbaseEnoteRecordV1> {
     let enote_ephemeral_privkey = EnoteEphemeralKey::derive(
         anchor_norm,
         &InputContext::new_coinbase(enote.block_index),
@@ -136,12 +238,11 @@ pub fn try_scan_carrot_coinbase_enote_sender_with_ephemeral_key(
     enote: &CarrotCoinbaseEnoteV1,
     destination: &CarrotDestinationV1,
     enote_ephemeral_privkey: &EnoteEphemeralKey,
-) -> Option<(OnetimeExtensionG, OnetimeExtensionT)> {
+) -> Result<CarrotCoinbaseEnoteRecordV1> {
     // s_sr = d_e ConvertPointE(K^j_v)
-    let s_sender_receiver_unctx = MontgomeryECDH::derive_as_sender(
-        enote_ephemeral_privkey,
-        &destination.address_view_pubkey,
-    )?;
+    let s_sender_receiver_unctx =
+        MontgomeryECDH::derive_as_sender(enote_ephemeral_privkey, &destination.address_view_pubkey)
+            .ok_or(Error::new(ErrorKind::InvalidPoint))?;
 
     let (sender_extension_g, sender_extension_t, address_spend_pubkey) =
         try_scan_carrot_coinbase_enote_checked(
@@ -151,22 +252,35 @@ pub fn try_scan_carrot_coinbase_enote_sender_with_ephemeral_key(
         )?;
 
     if &address_spend_pubkey != &destination.address_spend_pubkey {
-        return None;
+        return Err(Error::new(ErrorKind::AddressSpendPubkeyMismatch));
     }
 
-    Some((sender_extension_g, sender_extension_t))
+    Ok(CarrotCoinbaseEnoteRecordV1 {
+        enote: enote.clone(),
+        sender_extension_g,
+        sender_extension_t,
+        address_spend_pubkey,
+    })
 }
 
 pub fn try_scan_carrot_coinbase_enote_receiver(
     enote: &CarrotCoinbaseEnoteV1,
     s_sender_receiver_unctx: &MontgomeryECDH,
     main_address_spend_pubkeys: &[AddressSpendPubkey],
-) -> Option<(OnetimeExtensionG, OnetimeExtensionT, AddressSpendPubkey)> {
-    try_scan_carrot_coinbase_enote_checked(
-        enote,
-        s_sender_receiver_unctx,
-        main_address_spend_pubkeys,
-    )
+) -> Result<CarrotCoinbaseEnoteRecordV1> {
+    let (sender_extension_g, sender_extension_t, address_spend_pubkey) =
+        try_scan_carrot_coinbase_enote_checked(
+            enote,
+            s_sender_receiver_unctx,
+            main_address_spend_pubkeys,
+        )?;
+
+    Ok(CarrotCoinbaseEnoteRecordV1 {
+        enote: enote.clone(),
+        sender_extension_g,
+        sender_extension_t,
+        address_spend_pubkey,
+    })
 }
 
 pub fn try_scan_carrot_enote_external_sender_with_anchor_norm(
@@ -175,13 +289,7 @@ pub fn try_scan_carrot_enote_external_sender_with_anchor_norm(
     destination: &CarrotDestinationV1,
     anchor_norm: &JanusAnchor,
     check_pid: bool,
-) -> Option<(
-    OnetimeExtensionG,
-    OnetimeExtensionT,
-    Amount,
-    AmountBlindingKey,
-    CarrotEnoteType,
-)> {
+) -> Result<CarrotExternalEnoteRecordV1> {
     let enote_ephemeral_privkey = EnoteEphemeralKey::derive(
         anchor_norm,
         &InputContext::new_ringct(&enote.tx_first_key_image),
@@ -204,18 +312,11 @@ pub fn try_scan_carrot_enote_external_sender_with_ephemeral_key(
     destination: &CarrotDestinationV1,
     enote_ephemeral_privkey: &EnoteEphemeralKey,
     check_pid: bool,
-) -> Option<(
-    OnetimeExtensionG,
-    OnetimeExtensionT,
-    Amount,
-    AmountBlindingKey,
-    CarrotEnoteType,
-)> {
+) -> Result<CarrotExternalEnoteRecordV1> {
     // s_sr = d_e ConvertPointE(K^j_v)
-    let s_sender_receiver_unctx = MontgomeryECDH::derive_as_sender(
-        enote_ephemeral_privkey,
-        &destination.address_view_pubkey,
-    )?;
+    let s_sender_receiver_unctx =
+        MontgomeryECDH::derive_as_sender(enote_ephemeral_privkey, &destination.address_view_pubkey)
+            .ok_or(Error::new(ErrorKind::InvalidPoint))?;
 
     try_scan_carrot_enote_external_sender_with_shared_secret(
         enote,
@@ -232,13 +333,13 @@ pub fn try_scan_carrot_enote_external_sender_with_shared_secret(
     destination: &CarrotDestinationV1,
     s_sender_receiver_unctx: &MontgomeryECDH,
     check_pid: bool,
-) -> Option<(
-    OnetimeExtensionG,
-    OnetimeExtensionT,
-    Amount,
-    AmountBlindingKey,
-    CarrotEnoteType,
-)> {
+) -> Result<CarrotExternalEnoteRecordV1> {
+    // Janus protection recomputes D_e against the main address only if the destination is one
+    let main_address_spend_pubkeys = if destination.is_subaddress {
+        &[]
+    } else {
+        core::slice::from_ref(&destination.address_spend_pubkey)
+    };
     let (
         sender_extension_g,
         sender_extension_t,
@@ -253,26 +354,30 @@ pub fn try_scan_carrot_enote_external_sender_with_shared_secret(
         enote,
         encrypted_payment_id,
         s_sender_receiver_unctx,
-        core::slice::from_ref(&destination.address_spend_pubkey),
+        main_address_spend_pubkeys,
     )?;
 
     if !verified_normal_janus {
-        return None;
+        return Err(Error::new(ErrorKind::JanusProtectionFailed));
     } else if recovered_address_spend_pubkey != destination.address_spend_pubkey {
-        return None;
+        return Err(Error::new(ErrorKind::AddressSpendPubkeyMismatch));
     } else if check_pid && recovered_payment_id != destination.payment_id {
-        return None;
+        return Err(Error::new(ErrorKind::PaymentIdMismatch));
     } else if recovered_enote_type != CarrotEnoteType::Payment {
-        return None;
+        return Err(Error::new(ErrorKind::WrongEnoteType));
     }
 
-    Some((
+    Ok(CarrotExternalEnoteRecordV1 {
+        enote: enote.clone(),
+        encrypted_payment_id: encrypted_payment_id.cloned(),
         sender_extension_g,
         sender_extension_t,
+        address_spend_pubkey: recovered_address_spend_pubkey,
         amount,
         amount_blinding_factor,
-        recovered_enote_type,
-    ))
+        payment_id: recovered_payment_id,
+        enote_type: recovered_enote_type,
+    })
 }
 
 pub fn try_scan_carrot_enote_external_receiver<VI: ViewIncomingKeyDevice>(
@@ -281,15 +386,7 @@ pub fn try_scan_carrot_enote_external_receiver<VI: ViewIncomingKeyDevice>(
     s_sender_receiver_unctx: &MontgomeryECDH,
     main_address_spend_pubkeys: &[AddressSpendPubkey],
     k_view_dev: &VI,
-) -> Option<(
-    OnetimeExtensionG,
-    OnetimeExtensionT,
-    AddressSpendPubkey,
-    Amount,
-    AmountBlindingKey,
-    PaymentId,
-    CarrotEnoteType,
-)> {
+) -> Result<CarrotExternalEnoteRecordV1> {
     let (
         sender_extension_g,
         sender_extension_t,
@@ -315,13 +412,15 @@ pub fn try_scan_carrot_enote_external_receiver<VI: ViewIncomingKeyDevice>(
                 &enote.onetime_address,
                 k_view_dev,
                 &nominal_janus_anchor,
-            )
+            )?
         }
     {
-        return None;
+        return Err(Error::new(ErrorKind::JanusProtectionFailed));
     }
 
-    Some((
+    Ok(CarrotExternalEnoteRecordV1 {
+        enote: enote.clone(),
+        encrypted_payment_id: encrypted_payment_id.cloned(),
         sender_extension_g,
         sender_extension_t,
         address_spend_pubkey,
@@ -329,40 +428,137 @@ pub fn try_scan_carrot_enote_external_receiver<VI: ViewIncomingKeyDevice>(
         amount_blinding_factor,
         payment_id,
         enote_type,
-    ))
+    })
 }
 
 pub fn try_scan_carrot_enote_internal_receiver<VB: ViewBalanceSecretDevice>(
     enote: &CarrotEnoteV1,
     s_view_balance_dev: &VB,
-) -> Option<(
-    OnetimeExtensionG,
-    OnetimeExtensionT,
-    AddressSpendPubkey,
-    Amount,
-    AmountBlindingKey,
-    CarrotEnoteType,
-    JanusAnchor,
-)> {
+) -> Result<CarrotInternalEnoteRecordV1> {
     // input_context
     let input_context = InputContext::new_ringct(&enote.tx_first_key_image);
 
     // vt = H_3(s_sr || input_context || Ko)
-    let nominal_view_tag = s_view_balance_dev
-        .make_internal_view_tag(&input_context, &enote.onetime_address)
-        .ok()?;
+    let nominal_view_tag =
+        s_view_balance_dev.make_internal_view_tag(&input_context, &enote.onetime_address)?;
 
     // test view tag
     if nominal_view_tag != enote.view_tag {
-        return None;
+        return Err(Error::new(ErrorKind::ViewTagMismatch));
     }
 
     // s^ctx_sr = H_32(s_vb, D_e, input_context)
     let s_sender_receiver = s_view_balance_dev
-        .make_internal_sender_receiver_secret(&enote.enote_ephemeral_pubkey, &input_context)
-        .ok()?;
-
-    unsafe { try_scan_carrot_enote_internal_burnt(enote, &s_sender_receiver) }
+        .make_internal_sender_receiver_secret(&enote.enote_ephemeral_pubkey, &input_context)?;
 
     // janus protection checks are not needed for internal scans
+    let (
+        sender_extension_g,
+        sender_extension_t,
+        address_spend_pubkey,
+        amount,
+        amount_blinding_factor,
+        enote_type,
+        internal_message,
+    ) = unsafe { try_scan_carrot_enote_internal_burnt(enote, &s_sender_receiver) }?;
+
+    Ok(CarrotInternalEnoteRecordV1 {
+        enote: enote.clone(),
+        sender_extension_g,
+        sender_extension_t,
+        address_spend_pubkey,
+        amount,
+        amount_blinding_factor,
+        enote_type,
+        internal_message,
+    })
+}
+
+#[cfg(feature = "alloc")]
+fn lookup_and_expand_subaddress<G: GenerateAddressSecretDevice>(
+    address_spend_pubkey: &AddressSpendPubkey,
+    subaddress_table: &mut SubaddressTable<G>,
+) -> Result<SubaddressIndexExtended> {
+    // (j_major, j_minor) of K^j_s
+    let subaddress_index = *subaddress_table
+        .get(address_spend_pubkey)
+        .ok_or(Error::new(ErrorKind::AddressSpendPubkeyMismatch))?;
+
+    // slide the lookahead window past the index we just received to
+    subaddress_table
+        .expand(&subaddress_index.index)
+        .ok_or(Error::new(ErrorKind::DeviceError))?;
+
+    Ok(subaddress_index)
+}
+
+#[cfg(feature = "alloc")]
+pub fn try_scan_carrot_coinbase_enote_receiver_with_subaddress_table<
+    G: GenerateAddressSecretDevice,
+>(
+    enote: &CarrotCoinbaseEnoteV1,
+    s_sender_receiver_unctx: &MontgomeryECDH,
+    subaddress_table: &mut SubaddressTable<G>,
+) -> Result<CarrotIndexedCoinbaseEnoteRecordV1> {
+    let record = try_scan_carrot_coinbase_enote_receiver(
+        enote,
+        s_sender_receiver_unctx,
+        subaddress_table.main_address_spend_pubkeys(),
+    )?;
+
+    let subaddress_index =
+        lookup_and_expand_subaddress(&record.address_spend_pubkey, subaddress_table)?;
+
+    Ok(CarrotIndexedCoinbaseEnoteRecordV1 {
+        record,
+        subaddress_index,
+    })
+}
+
+#[cfg(feature = "alloc")]
+pub fn try_scan_carrot_enote_external_receiver_with_subaddress_table<
+    VI: ViewIncomingKeyDevice,
+    G: GenerateAddressSecretDevice,
+>(
+    enote: &CarrotEnoteV1,
+    encrypted_payment_id: Option<&EncryptedPaymentId>,
+    s_sender_receiver_unctx: &MontgomeryECDH,
+    subaddress_table: &mut SubaddressTable<G>,
+    k_view_dev: &VI,
+) -> Result<CarrotIndexedExternalEnoteRecordV1> {
+    let record = try_scan_carrot_enote_external_receiver(
+        enote,
+        encrypted_payment_id,
+        s_sender_receiver_unctx,
+        subaddress_table.main_address_spend_pubkeys(),
+        k_view_dev,
+    )?;
+
+    let subaddress_index =
+        lookup_and_expand_subaddress(&record.address_spend_pubkey, subaddress_table)?;
+
+    Ok(CarrotIndexedExternalEnoteRecordV1 {
+        record,
+        subaddress_index,
+    })
+}
+
+#[cfg(feature = "alloc")]
+pub fn try_scan_carrot_enote_internal_receiver_with_subaddress_table<
+    VB: ViewBalanceSecretDevice,
+    G: GenerateAddressSecretDevice,
+>(
+    enote: &CarrotEnoteV1,
+    s_view_balance_dev: &VB,
+    subaddress_table: &mut SubaddressTable<G>,
+) -> Result<CarrotIndexedInternalEnoteRecordV1> {
+    let record = try_scan_carrot_enote_internal_receiver(enote, s_view_balance_dev)?;
+
+    let subaddress_index =
+        lookup_and_expand_subaddress(&record.address_spend_pubkey, subaddress_table)?;
+
+    Ok(CarrotIndexedInternalEnoteRecordV1 {
+        record,
+        subaddress_index,
+    })
 }
@@ -0,0 +1,221 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::device::{GenerateAddressSecretDevice, ViewIncomingKeyDevice};
+use crate::enote::*;
+use crate::scan::{self, Error, ErrorKind, Result};
+use crate::subaddress_table::SubaddressTable;
+use crate::*;
+
+/// Owned enote found while batch scanning
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CarrotBatchOwnedEnoteV1 {
+    /// index of the enote in the scanned batch
+    pub batch_index: usize,
+    /// record of the enote, tagged with its subaddress index
+    pub record: CarrotIndexedExternalEnoteRecordV1,
+}
+
+/// Result of batch scanning enotes
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CarrotBatchScanResultV1 {
+    /// owned enotes, in batch order
+    pub owned_enotes: Vec<CarrotBatchOwnedEnoteV1>,
+    /// batch indices of enotes addressed to us which failed Janus protection: this is likely an
+    /// attempted Janus attack, and the user should be warned about it
+    pub janus_failures: Vec<usize>,
+}
+
+// number of D_e per batched device call when scanning in parallel
+#[cfg(feature = "rayon")]
+const PAR_SCAN_CHUNK_SIZE: usize = 256;
+
+type BatchScanHits = Vec<(usize, Result<CarrotExternalEnoteRecordV1>)>;
+
+// split the batch into runs of adjacent enotes sharing a D_e, e.g. the outputs of 2-output txs
+fn group_by_enote_ephemeral_pubkey(
+    enotes: &[(CarrotEnoteV1, Option<EncryptedPaymentId>)],
+) -> Vec<Range<usize>> {
+    let mut groups = Vec::new();
+    let mut begin = 0;
+    for i in 1..=enotes.len() {
+        if i == enotes.len()
+            || enotes[i].0.enote_ephemeral_pubkey != enotes[begin].0.enote_ephemeral_pubkey
+        {
+            groups.push(begin..i);
+            begin = i;
+        }
+    }
+    groups
+}
+
+// s_sr = k_v D_e for each group, in a single device call
+fn make_group_shared_keys<VI: ViewIncomingKeyDevice>(
+    enotes: &[(CarrotEnoteV1, Option<EncryptedPaymentId>)],
+    groups: &[Range<usize>],
+    k_view_dev: &VI,
+) -> Result<Vec<MontgomeryECDH>> {
+    let enote_ephemeral_pubkeys: Vec<EnoteEphemeralPubkey> = groups
+        .iter()
+        .map(|group| enotes[group.start].0.enote_ephemeral_pubkey.clone())
+        .collect();
+    let mut s_sender_receiver_unctx = Vec::new();
+    s_sender_receiver_unctx.resize_with(groups.len(), Default::default);
+    k_view_dev.view_key_scalar_mult_x25519_batch(
+        &enote_ephemeral_pubkeys,
+        &mut s_sender_receiver_unctx,
+    )?;
+    Ok(s_sender_receiver_unctx)
+}
+
+fn scan_group<VI: ViewIncomingKeyDevice>(
+    enotes: &[(CarrotEnoteV1, Option<EncryptedPaymentId>)],
+    group: Range<usize>,
+    s_sender_receiver_unctx: &MontgomeryECDH,
+    main_address_spend_pubkeys: &[AddressSpendPubkey],
+    k_view_dev: &VI,
+) -> BatchScanHits {
+    let mut hits = Vec::new();
+    for batch_index in group {
+        let (enote, encrypted_payment_id) = &enotes[batch_index];
+        match scan::try_scan_carrot_enote_external_receiver(
+            enote,
+            encrypted_payment_id.as_ref(),
+            s_sender_receiver_unctx,
+            main_address_spend_pubkeys,
+            k_view_dev,
+        ) {
+            Err(error)
+                if error.kind() != ErrorKind::DeviceError
+                    && error.kind() != ErrorKind::JanusProtectionFailed => {}
+            result => hits.push((batch_index, result)),
+        }
+    }
+    hits
+}
+
+fn scan_groups<VI: ViewIncomingKeyDevice>(
+    enotes: &[(CarrotEnoteV1, Option<EncryptedPaymentId>)],
+    groups: &[Range<usize>],
+    main_address_spend_pubkeys: &[AddressSpendPubkey],
+    k_view_dev: &VI,
+) -> Result<BatchScanHits> {
+    let s_sender_receiver_unctx = make_group_shared_keys(enotes, groups, k_view_dev)?;
+    Ok(groups
+        .iter()
+        .zip(s_sender_receiver_unctx.iter())
+        .flat_map(|(group, s_sender_receiver_unctx)| {
+            scan_group(
+                enotes,
+                group.clone(),
+                s_sender_receiver_unctx,
+                main_address_spend_pubkeys,
+                k_view_dev,
+            )
+        })
+        .collect())
+}
+
+fn collect_batch_hits<G: GenerateAddressSecretDevice>(
+    hits: impl Iterator<Item = (usize, Result<CarrotExternalEnoteRecordV1>)>,
+    subaddress_table: &mut SubaddressTable<G>,
+) -> Result<CarrotBatchScanResultV1> {
+    let mut scan_result = CarrotBatchScanResultV1::default();
+
+    // enotes which passed Janus checks, but whose K^j_s isn't (yet) in the table
+    let mut pending = Vec::new();
+    for (batch_index, result) in hits {
+        match result {
+            Ok(record) => pending.push((batch_index, record)),
+            Err(error) if error.kind() == ErrorKind::JanusProtectionFailed => {
+                scan_result.janus_failures.push(batch_index)
+            }
+            Err(error) => return Err(error),
+        }
+    }
+
+    // resolve subaddress indices in batch order, retrying unresolved enotes as long as the
+    // lookahead window keeps growing, since a hit on a high index may uncover later enotes
+    loop {
+        let table_len = subaddress_table.len();
+        let mut unresolved = Vec::new();
+        for (batch_index, record) in pending {
+            let Some(subaddress_index) =
+                subaddress_table.get(&record.address_spend_pubkey).copied()
+            else {
+                unresolved.push((batch_index, record));
+                continue;
+            };
+            subaddress_table
+                .expand(&subaddress_index.index)
+                .ok_or(Error::new(ErrorKind::DeviceError))?;
+            scan_result.owned_enotes.push(CarrotBatchOwnedEnoteV1 {
+                batch_index,
+                record: CarrotIndexedExternalEnoteRecordV1 {
+                    record,
+                    subaddress_index,
+                },
+            });
+        }
+
+        if unresolved.is_empty() || subaddress_table.len() == table_len {
+            break;
+        }
+        pending = unresolved;
+    }
+
+    scan_result
+        .owned_enotes
+        .sort_by_key(|owned_enote| owned_enote.batch_index);
+
+    Ok(scan_result)
+}
+
+/// Scan a batch of enotes, each with its transaction's encrypted payment ID (if any), with the
+/// view-incoming key, returning the owned subset tagged with their subaddress indices. The ECDH
+/// exchanges are done in one batched device call, once per run of adjacent enotes sharing an
+/// enote ephemeral pubkey.
+pub fn try_scan_carrot_enotes_external_receiver_batch<VI, G>(
+    enotes: &[(CarrotEnoteV1, Option<EncryptedPaymentId>)],
+    subaddress_table: &mut SubaddressTable<G>,
+    k_view_dev: &VI,
+) -> Result<CarrotBatchScanResultV1>
+where
+    VI: ViewIncomingKeyDevice,
+    G: GenerateAddressSecretDevice,
+{
+    let groups = group_by_enote_ephemeral_pubkey(enotes);
+    let hits = scan_groups(
+        enotes,
+        &groups,
+        subaddress_table.main_address_spend_pubkeys(),
+        k_view_dev,
+    )?;
+
+    collect_batch_hits(hits.into_iter(), subaddress_table)
+}
+
+/// Same as [`try_scan_carrot_enotes_external_receiver_batch`], but spreads the scanning across
+/// the rayon thread pool, with one batched device call per chunk of enotes
+#[cfg(feature = "rayon")]
+pub fn par_try_scan_carrot_enotes_external_receiver_batch<VI, G>(
+    enotes: &[(CarrotEnoteV1, Option<EncryptedPaymentId>)],
+    subaddress_table: &mut SubaddressTable<G>,
+    k_view_dev: &VI,
+) -> Result<CarrotBatchScanResultV1>
+where
+    VI: ViewIncomingKeyDevice + Sync,
+    G: GenerateAddressSecretDevice,
+{
+    let groups = group_by_enote_ephemeral_pubkey(enotes);
+    let main_address_spend_pubkeys = subaddress_table.main_address_spend_pubkeys();
+    let hits: Vec<BatchScanHits> = groups
+        .par_chunks(PAR_SCAN_CHUNK_SIZE)
+        .map(|groups| scan_groups(enotes, groups, main_address_spend_pubkeys, k_view_dev))
+        .collect::<Result<_>>()?;
+
+    collect_batch_hits(hits.into_iter().flatten(), subaddress_table)
+}
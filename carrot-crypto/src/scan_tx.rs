@@ -0,0 +1,157 @@
+use alloc::vec::Vec;
+
+use crate::device::{GenerateAddressSecretDevice, ViewBalanceSecretDevice, ViewIncomingKeyDevice};
+use crate::enote::*;
+use crate::scan::{self, Error, ErrorKind, Result};
+use crate::subaddress_table::SubaddressTable;
+use crate::*;
+
+/// Owned enote found while scanning a transaction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CarrotTxOwnedEnoteV1 {
+    /// index of the enote in the transaction's outputs
+    pub output_index: usize,
+    /// record of the enote
+    pub record: CarrotOwnedEnoteRecordV1,
+}
+
+/// Result of scanning all enotes of a transaction
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CarrotTxScanResultV1 {
+    /// owned enotes, in output order
+    pub owned_enotes: Vec<CarrotTxOwnedEnoteV1>,
+    /// output indices of enotes addressed to us which failed Janus protection: this is likely an
+    /// attempted Janus attack, and the user should be warned about it
+    pub janus_failures: Vec<usize>,
+}
+
+fn try_scan_carrot_tx_impl<VI, G, F>(
+    enotes: &[CarrotEnoteV1],
+    encrypted_payment_id: Option<&EncryptedPaymentId>,
+    subaddress_table: &mut SubaddressTable<G>,
+    k_view_dev: &VI,
+    mut try_scan_internal: F,
+) -> Result<CarrotTxScanResultV1>
+where
+    VI: ViewIncomingKeyDevice,
+    G: GenerateAddressSecretDevice,
+    F: FnMut(&CarrotEnoteV1, &mut SubaddressTable<G>) -> Result<CarrotIndexedInternalEnoteRecordV1>,
+{
+    let mut scan_result = CarrotTxScanResultV1::default();
+
+    // 2-output transactions share a single D_e, so only do the ECDH once per distinct D_e
+    let mut ecdh_cache: Vec<(&EnoteEphemeralPubkey, MontgomeryECDH)> = Vec::new();
+
+    for (output_index, enote) in enotes.iter().enumerate() {
+        // s_sr = k_v D_e
+        let cached_ecdh = ecdh_cache
+            .iter()
+            .find(|(enote_ephemeral_pubkey, _)| {
+                *enote_ephemeral_pubkey == &enote.enote_ephemeral_pubkey
+            })
+            .map(|(_, s_sender_receiver_unctx)| s_sender_receiver_unctx.clone());
+        let s_sender_receiver_unctx = match cached_ecdh {
+            Some(s_sender_receiver_unctx) => s_sender_receiver_unctx,
+            None => {
+                let s_sender_receiver_unctx =
+                    scan::make_carrot_uncontextualized_shared_key_receiver(
+                        k_view_dev,
+                        &enote.enote_ephemeral_pubkey,
+                    )?;
+                ecdh_cache.push((
+                    &enote.enote_ephemeral_pubkey,
+                    s_sender_receiver_unctx.clone(),
+                ));
+                s_sender_receiver_unctx
+            }
+        };
+
+        // try external path first, which covers payments and special self-sends
+        let external_error =
+            match scan::try_scan_carrot_enote_external_receiver_with_subaddress_table(
+                enote,
+                encrypted_payment_id,
+                &s_sender_receiver_unctx,
+                subaddress_table,
+                k_view_dev,
+            ) {
+                Ok(record) => {
+                    scan_result.owned_enotes.push(CarrotTxOwnedEnoteV1 {
+                        output_index,
+                        record: CarrotOwnedEnoteRecordV1::External(record),
+                    });
+                    continue;
+                }
+                Err(error) if error.kind() == ErrorKind::DeviceError => return Err(error),
+                Err(error) => error,
+            };
+
+        // then the internal path, if the wallet holds the keys for it
+        match try_scan_internal(enote, subaddress_table) {
+            Ok(record) => {
+                scan_result.owned_enotes.push(CarrotTxOwnedEnoteV1 {
+                    output_index,
+                    record: CarrotOwnedEnoteRecordV1::Internal(record),
+                });
+                continue;
+            }
+            Err(error) if error.kind() == ErrorKind::DeviceError => return Err(error),
+            Err(_) => (),
+        }
+
+        // any other failure just means the enote isn't ours, unless it's a Janus failure
+        if external_error.kind() == ErrorKind::JanusProtectionFailed {
+            scan_result.janus_failures.push(output_index);
+        }
+    }
+
+    Ok(scan_result)
+}
+
+/// Scan all enotes of a transaction with the view-incoming key, finding payments and special
+/// self-sends. Internal self-sends can't be found without the view-balance secret.
+pub fn try_scan_carrot_tx_external_receiver<VI, G>(
+    enotes: &[CarrotEnoteV1],
+    encrypted_payment_id: Option<&EncryptedPaymentId>,
+    subaddress_table: &mut SubaddressTable<G>,
+    k_view_dev: &VI,
+) -> Result<CarrotTxScanResultV1>
+where
+    VI: ViewIncomingKeyDevice,
+    G: GenerateAddressSecretDevice,
+{
+    try_scan_carrot_tx_impl(
+        enotes,
+        encrypted_payment_id,
+        subaddress_table,
+        k_view_dev,
+        |_, _| Err(Error::new(ErrorKind::ViewTagMismatch)),
+    )
+}
+
+/// Scan all enotes of a transaction with the view-balance secret, finding payments, special
+/// self-sends, and internal self-sends
+pub fn try_scan_carrot_tx_receiver<VB, G>(
+    enotes: &[CarrotEnoteV1],
+    encrypted_payment_id: Option<&EncryptedPaymentId>,
+    subaddress_table: &mut SubaddressTable<G>,
+    s_view_balance_dev: &VB,
+) -> Result<CarrotTxScanResultV1>
+where
+    VB: ViewBalanceSecretDevice + ViewIncomingKeyDevice,
+    G: GenerateAddressSecretDevice,
+{
+    try_scan_carrot_tx_impl(
+        enotes,
+        encrypted_payment_id,
+        subaddress_table,
+        s_view_balance_dev,
+        |enote, subaddress_table| {
+            scan::try_scan_carrot_enote_internal_receiver_with_subaddress_table(
+                enote,
+                s_view_balance_dev,
+                subaddress_table,
+            )
+        },
+    )
+}
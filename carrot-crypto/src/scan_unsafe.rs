@@ -1,6 +1,7 @@
 use crate::as_crypto::AsMontgomeryPoint;
 use crate::device::ViewIncomingKeyDevice;
 use crate::enote::{CarrotCoinbaseEnoteV1, CarrotEnoteV1};
+use crate::scan::{Error, ErrorKind, Result};
 use crate::*;
 
 unsafe fn scan_non_coinbase_dest_info(
@@ -9,7 +10,7 @@ unsafe fn scan_non_coinbase_dest_info(
     encrypted_janus_anchor: &EncryptedJanusAnchor,
     encrypted_payment_id: Option<&EncryptedPaymentId>,
     s_sender_receiver: &SenderReceiverSecret,
-) -> Option<(
+) -> Result<(
     OnetimeExtensionG,
     OnetimeExtensionT,
     AddressSpendPubkey,
@@ -28,7 +29,8 @@ unsafe fn scan_non_coinbase_dest_info(
 
     // K^j_s = Ko - K^o_ext = Ko - (k^g_o G + k^t_o T)
     let address_spend_pubkey =
-        AddressSpendPubkey::recover_from_extension(onetime_address, &sender_extension)?;
+        AddressSpendPubkey::recover_from_extension(onetime_address, &sender_extension)
+            .ok_or(Error::new(ErrorKind::InvalidPoint))?;
 
     // pid = pid_enc XOR m_pid, if applicable
     let nominal_payment_id = match encrypted_payment_id {
@@ -41,7 +43,7 @@ unsafe fn scan_non_coinbase_dest_info(
     // anchor = anchor_enc XOR m_anchor
     let janus_anchor = encrypted_janus_anchor.decrypt(&s_sender_receiver, &onetime_address);
 
-    Some((
+    Ok((
         sender_extension_g,
         sender_extension_t,
         address_spend_pubkey,
@@ -54,7 +56,7 @@ pub unsafe fn try_scan_carrot_coinbase_enote_no_janus(
     enote: &CarrotCoinbaseEnoteV1,
     s_sender_receiver_unctx: &MontgomeryECDH,
     main_address_spend_pubkeys: &[AddressSpendPubkey],
-) -> Option<(
+) -> Result<(
     OnetimeExtensionG,
     OnetimeExtensionT,
     AddressSpendPubkey,
@@ -69,7 +71,7 @@ pub unsafe fn try_scan_carrot_coinbase_enote_no_janus(
         &input_context,
         &enote.onetime_address,
     ) {
-        return None;
+        return Err(Error::new(ErrorKind::ViewTagMismatch));
     }
 
     // s^ctx_sr = H_32(s_sr, D_e, input_context)
@@ -100,7 +102,8 @@ pub unsafe fn try_scan_carrot_coinbase_enote_no_janus(
 
         // K^j_s = Ko - K^o_ext
         let recovered_address_spend_pubkey =
-            AddressSpendPubkey::recover_from_extension(&enote.onetime_address, &sender_extension)?;
+            AddressSpendPubkey::recover_from_extension(&enote.onetime_address, &sender_extension)
+                .ok_or(Error::new(ErrorKind::InvalidPoint))?;
 
         // if hit on some K^0_s:
         if &recovered_address_spend_pubkey == main_address_spend_pubkey {
@@ -109,7 +112,7 @@ pub unsafe fn try_scan_carrot_coinbase_enote_no_janus(
                 .anchor_enc
                 .decrypt(&s_sender_receiver, &enote.onetime_address);
 
-            return Some((
+            return Ok((
                 sender_extension_g,
                 sender_extension_t,
                 recovered_address_spend_pubkey,
@@ -118,14 +121,14 @@ pub unsafe fn try_scan_carrot_coinbase_enote_no_janus(
         }
     }
 
-    None
+    Err(Error::new(ErrorKind::AddressSpendPubkeyMismatch))
 }
 
 pub unsafe fn try_scan_carrot_enote_external_no_janus(
     enote: &CarrotEnoteV1,
     encrypted_payment_id: Option<&EncryptedPaymentId>,
     s_sender_receiver_unctx: &MontgomeryECDH,
-) -> Option<(
+) -> Result<(
     OnetimeExtensionG,
     OnetimeExtensionT,
     AddressSpendPubkey,
@@ -144,7 +147,7 @@ pub unsafe fn try_scan_carrot_enote_external_no_janus(
         &input_context,
         &enote.onetime_address,
     ) {
-        return None;
+        return Err(Error::new(ErrorKind::ViewTagMismatch));
     }
 
     // s^ctx_sr = H_32(s_sr, D_e, input_context)
@@ -178,9 +181,10 @@ pub unsafe fn try_scan_carrot_enote_external_no_janus(
         &enote.onetime_address,
         &address_spend_pubkey,
         &enote.amount_commitment,
-    )?;
+    )
+    .ok_or(Error::new(ErrorKind::AmountCommitmentMismatch))?;
 
-    Some((
+    Ok((
         sender_extension_g,
         sender_extension_t,
         address_spend_pubkey,
@@ -195,7 +199,7 @@ pub unsafe fn try_scan_carrot_enote_external_no_janus(
 pub unsafe fn try_scan_carrot_enote_internal_burnt(
     enote: &CarrotEnoteV1,
     s_sender_receiver: &SenderReceiverSecret,
-) -> Option<(
+) -> Result<(
     OnetimeExtensionG,
     OnetimeExtensionT,
     AddressSpendPubkey,
@@ -222,9 +226,10 @@ pub unsafe fn try_scan_carrot_enote_internal_burnt(
         &enote.onetime_address,
         &address_spend_pubkey,
         &enote.amount_commitment,
-    )?;
+    )
+    .ok_or(Error::new(ErrorKind::AmountCommitmentMismatch))?;
 
-    Some((
+    Ok((
         sender_extension_g,
         sender_extension_t,
         address_spend_pubkey,
@@ -273,7 +278,7 @@ pub unsafe fn verify_carrot_special_janus_protection<VI>(
     onetime_address: &OutputPubkey,
     k_view_dev: &VI,
     nominal_janus_anchor: &JanusAnchor,
-) -> bool
+) -> Result<bool>
 where
     VI: ViewIncomingKeyDevice,
 {
@@ -281,14 +286,10 @@ where
     let input_context = InputContext::new_ringct(tx_first_key_image);
 
     // anchor_sp = H_16(D_e, input_context, Ko, k_v)
-    let Ok(expected_special_anchor) = k_view_dev.make_janus_anchor_special(
-        enote_ephemeral_pubkey,
-        &input_context,
-        onetime_address,
-    ) else {
-        return false;
-    };
+    let expected_special_anchor = k_view_dev
+        .make_janus_anchor_special(enote_ephemeral_pubkey, &input_context, onetime_address)
+        .map_err(|_| Error::new(ErrorKind::DeviceError))?;
 
     // attempt special janus check: anchor_sp ?= anchor'
-    return &expected_special_anchor == nominal_janus_anchor;
+    Ok(&expected_special_anchor == nominal_janus_anchor)
 }
@@ -0,0 +1,351 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::destination::*;
+use crate::device::GenerateAddressSecretDevice;
+use crate::legacy::LegacySubaddressExtension;
+use crate::type_macros::*;
+use crate::*;
+
+/// Number of subaddress indices derived past the highest index seen so far
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SubaddressLookahead {
+    /// Number of major indices past the highest major index seen
+    pub major: u32,
+    /// Number of minor indices past the highest minor index seen in a given major index
+    pub minor: u32,
+}
+
+struct CarrotTableKeys<G: GenerateAddressSecretDevice> {
+    account_spend_pubkey: AddressSpendPubkey,
+    account_view_pubkey: AddressViewPubkey,
+    s_generate_address_dev: G,
+}
+
+struct LegacyTableKeys {
+    k_view_incoming: ViewIncomingKey,
+    account_spend_pubkey: AddressSpendPubkey,
+}
+
+/// Lookahead table mapping address spend pubkeys K^j_s to their subaddress indices, for
+/// resolving which address of an account received a scanned enote
+pub struct SubaddressTable<G: GenerateAddressSecretDevice> {
+    carrot: Option<CarrotTableKeys<G>>,
+    legacy: Option<LegacyTableKeys>,
+    lookahead: SubaddressLookahead,
+    main_address_spend_pubkeys: Vec<AddressSpendPubkey>,
+    // number of minor indices derived, for each major index derived
+    num_minor_indices: Vec<u32>,
+    entries: BTreeMap<[u8; 32], SubaddressIndexExtended>,
+}
+
+impl<G: GenerateAddressSecretDevice> SubaddressTable<G> {
+    /// Create a table for the addresses of a Carrot-derived account, with its initial window
+    /// derived. Returns None if the device fails to derive a subaddress.
+    pub fn new_carrot(
+        account_spend_pubkey: AddressSpendPubkey,
+        account_view_pubkey: AddressViewPubkey,
+        s_generate_address_dev: G,
+        lookahead: SubaddressLookahead,
+    ) -> Option<Self> {
+        let mut table = Self::new_empty(lookahead);
+        table
+            .main_address_spend_pubkeys
+            .push(account_spend_pubkey.clone());
+        table.carrot = Some(CarrotTableKeys {
+            account_spend_pubkey,
+            account_view_pubkey,
+            s_generate_address_dev,
+        });
+        table.expand(&SubaddressIndex::default())?;
+        Some(table)
+    }
+
+    /// Also track the legacy addresses of an account sharing this table, which are derived from
+    /// the legacy view key k_v and legacy account spend pubkey K_s
+    pub fn with_legacy(
+        mut self,
+        k_view_incoming: ViewIncomingKey,
+        account_spend_pubkey: AddressSpendPubkey,
+    ) -> Option<Self> {
+        self.main_address_spend_pubkeys
+            .push(account_spend_pubkey.clone());
+        self.legacy = Some(LegacyTableKeys {
+            k_view_incoming,
+            account_spend_pubkey,
+        });
+
+        // re-derive the existing window for the legacy addresses
+        let num_minor_indices = core::mem::take(&mut self.num_minor_indices);
+        for (major_index, num_minor) in num_minor_indices.iter().enumerate() {
+            for minor_index in 0..*num_minor {
+                self.insert_legacy(major_index as u32, minor_index)?;
+            }
+        }
+        self.num_minor_indices = num_minor_indices;
+
+        Some(self)
+    }
+
+    /// Lookahead window of this table
+    pub fn lookahead(&self) -> &SubaddressLookahead {
+        &self.lookahead
+    }
+
+    /// Account spend pubkeys K_s of all accounts in this table, i.e. their main address spend
+    /// pubkeys
+    pub fn main_address_spend_pubkeys(&self) -> &[AddressSpendPubkey] {
+        &self.main_address_spend_pubkeys
+    }
+
+    /// Number of address spend pubkeys currently in the table
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the table contains no address spend pubkeys
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Subaddress index of an address spend pubkey K^j_s, if it's in the table
+    pub fn get(
+        &self,
+        address_spend_pubkey: &AddressSpendPubkey,
+    ) -> Option<&SubaddressIndexExtended> {
+        self.entries
+            .get(&address_spend_pubkey.as_edwards_ref().to_bytes())
+    }
+
+    /// Grow the table so that the lookahead window extends past the given index. This should be
+    /// called whenever an enote is received to an index, so that enotes received to higher
+    /// indices are still found. Returns None if the device fails to derive a subaddress.
+    pub fn expand(&mut self, index: &SubaddressIndex) -> Option<()> {
+        let end_major = index.major.saturating_add(self.lookahead.major);
+        for major_index in 0..end_major {
+            let begin_minor = self
+                .num_minor_indices
+                .get(major_index as usize)
+                .copied()
+                .unwrap_or_default();
+            let end_minor = if major_index == index.major {
+                index.minor.saturating_add(self.lookahead.minor)
+            } else {
+                self.lookahead.minor
+            };
+
+            for minor_index in begin_minor..end_minor {
+                if self.carrot.is_some() {
+                    self.insert_carrot(major_index, minor_index)?;
+                }
+                if self.legacy.is_some() {
+                    self.insert_legacy(major_index, minor_index)?;
+                }
+            }
+
+            // record how far this major index is derived
+            if major_index as usize >= self.num_minor_indices.len() {
+                self.num_minor_indices.push(0);
+            }
+            let num_minor = &mut self.num_minor_indices[major_index as usize];
+            *num_minor = (*num_minor).max(end_minor);
+        }
+
+        Some(())
+    }
+
+    fn new_empty(lookahead: SubaddressLookahead) -> Self {
+        Self {
+            carrot: None,
+            legacy: None,
+            lookahead,
+            main_address_spend_pubkeys: Vec::new(),
+            num_minor_indices: Vec::new(),
+            entries: BTreeMap::new(),
+        }
+    }
+
+    fn insert(
+        &mut self,
+        address_spend_pubkey: &AddressSpendPubkey,
+        major_index: u32,
+        minor_index: u32,
+        derive_type: AddressDeriveType,
+    ) {
+        self.entries.insert(
+            address_spend_pubkey.as_edwards_ref().to_bytes(),
+            SubaddressIndexExtended {
+                index: SubaddressIndex {
+                    major: major_index,
+                    minor: minor_index,
+                },
+                derive_type,
+            },
+        );
+    }
+
+    fn insert_carrot(&mut self, major_index: u32, minor_index: u32) -> Option<()> {
+        let keys = self.carrot.as_ref()?;
+
+        // K^j_s = k^j_subscal * K_s, or K_s for the main address
+        let address_spend_pubkey = if major_index == 0 && minor_index == 0 {
+            keys.account_spend_pubkey.clone()
+        } else {
+            CarrotDestinationV1::make_subaddress(
+                &keys.account_spend_pubkey,
+                &keys.account_view_pubkey,
+                &keys.s_generate_address_dev,
+                major_index,
+                minor_index,
+            )?
+            .address_spend_pubkey
+        };
+
+        self.insert(
+            &address_spend_pubkey,
+            major_index,
+            minor_index,
+            AddressDeriveType::Carrot,
+        );
+        Some(())
+    }
+
+    fn insert_legacy(&mut self, major_index: u32, minor_index: u32) -> Option<()> {
+        let keys = self.legacy.as_ref()?;
+
+        // m = H_n("SubAddr" || IntToBytes8(0) || k_v || IntToBytes32(j_major) || IntToBytes32(j_minor))
+        let subaddr_extension =
+            LegacySubaddressExtension::derive(&keys.k_view_incoming, major_index, minor_index);
+
+        // K^j_s = K_s + m G
+        let address_spend_pubkey = AddressSpendPubkey::derive_legacy_subaddress_spend_pubkey(
+            &subaddr_extension,
+            &keys.account_spend_pubkey,
+        )?;
+
+        self.insert(
+            &address_spend_pubkey,
+            major_index,
+            minor_index,
+            AddressDeriveType::Legacy,
+        );
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::legacy::*;
+    use crate::random::*;
+    use crate::subaddress_table::*;
+
+    const LOOKAHEAD: SubaddressLookahead = SubaddressLookahead { major: 2, minor: 3 };
+
+    fn make_table(
+        account: &CarrotAccount,
+        legacy_account: &LegacyAccount,
+    ) -> SubaddressTable<GenerateAddressSecret> {
+        SubaddressTable::new_carrot(
+            account.account_spend_pubkey().clone(),
+            account.account_view_pubkey().clone(),
+            account.generate_address_secret().clone(),
+            LOOKAHEAD,
+        )
+        .unwrap()
+        .with_legacy(
+            legacy_account.view_incoming_key().clone(),
+            legacy_account.account_spend_pubkey().clone(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn subaddress_table_initial_window() {
+        let account = CarrotAccount::new_random_with_params(&mut rand_core::OsRng, ());
+        let legacy_account = LegacyAccount::new_random_with_params(&mut rand_core::OsRng, ());
+        let table = make_table(&account, &legacy_account);
+
+        assert_eq!(2 * 2 * 3, table.len());
+        for major_index in 0..LOOKAHEAD.major {
+            for minor_index in 0..LOOKAHEAD.minor {
+                let index = SubaddressIndex {
+                    major: major_index,
+                    minor: minor_index,
+                };
+                let address = match index.is_subaddress() {
+                    true => account.subaddress(major_index, minor_index).unwrap(),
+                    false => account.main_address(),
+                };
+                assert_eq!(
+                    Some(&SubaddressIndexExtended {
+                        index,
+                        derive_type: AddressDeriveType::Carrot
+                    }),
+                    table.get(&address.address_spend_pubkey)
+                );
+
+                let legacy_address = legacy_account.subaddress(major_index, minor_index);
+                assert_eq!(
+                    Some(&SubaddressIndexExtended {
+                        index,
+                        derive_type: AddressDeriveType::Legacy
+                    }),
+                    table.get(&legacy_address.address_spend_pubkey)
+                );
+            }
+        }
+
+        assert_eq!(
+            None,
+            table.get(
+                &account
+                    .subaddress(0, LOOKAHEAD.minor)
+                    .unwrap()
+                    .address_spend_pubkey
+            )
+        );
+        assert_eq!(
+            None,
+            table.get(
+                &account
+                    .subaddress(LOOKAHEAD.major, 0)
+                    .unwrap()
+                    .address_spend_pubkey
+            )
+        );
+    }
+
+    #[test]
+    fn subaddress_table_expand() {
+        let account = CarrotAccount::new_random_with_params(&mut rand_core::OsRng, ());
+        let legacy_account = LegacyAccount::new_random_with_params(&mut rand_core::OsRng, ());
+        let mut table = make_table(&account, &legacy_account);
+
+        // receiving to the last index in the window slides the window past it
+        let index = SubaddressIndex {
+            major: LOOKAHEAD.major - 1,
+            minor: LOOKAHEAD.minor - 1,
+        };
+        table.expand(&index).unwrap();
+
+        let next_minor = account
+            .subaddress(index.major, index.minor + LOOKAHEAD.minor - 1)
+            .unwrap();
+        assert!(table.get(&next_minor.address_spend_pubkey).is_some());
+        let next_major = account
+            .subaddress(index.major + LOOKAHEAD.major - 1, LOOKAHEAD.minor - 1)
+            .unwrap();
+        assert!(table.get(&next_major.address_spend_pubkey).is_some());
+        let next_legacy = legacy_account.subaddress(index.major, index.minor + LOOKAHEAD.minor - 1);
+        assert!(table.get(&next_legacy.address_spend_pubkey).is_some());
+
+        // other major indices only keep the default number of minor indices
+        let other_minor = account.subaddress(0, LOOKAHEAD.minor).unwrap();
+        assert!(table.get(&other_minor.address_spend_pubkey).is_none());
+
+        // expanding is idempotent
+        let len = table.len();
+        table.expand(&index).unwrap();
+        assert_eq!(len, table.len());
+    }
+}
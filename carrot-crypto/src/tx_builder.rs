@@ -0,0 +1,140 @@
+use alloc::vec::Vec;
+
+use crate::device::{ViewBalanceSecretDevice, ViewIncomingKeyDevice};
+use crate::output_set_finalization::*;
+use crate::payments::*;
+use crate::random::new_random;
+use crate::*;
+
+/// Finalized outputs of a transaction built by [`CarrotTxBuilderV1`]
+pub struct CarrotTxProposalV1 {
+    /// output enote proposals, sorted by K_o
+    pub output_enote_proposals: Vec<RCTOutputEnoteProposal>,
+    /// pid_enc
+    pub encrypted_payment_id: EncryptedPaymentId,
+    /// for each output enote proposal, the index of the recipient it pays, or None for the change
+    /// and dummy outputs
+    pub recipient_indices: Vec<Option<usize>>,
+}
+
+/// Builder of the outputs of a transaction which pays a list of recipients, and sends the change
+/// back to the sender
+pub struct CarrotTxBuilderV1 {
+    recipients: Vec<(CarrotDestinationV1, Amount)>,
+    change_address_spend_pubkey: AddressSpendPubkey,
+    input_amount: Amount,
+    fee: Amount,
+}
+
+impl CarrotTxBuilderV1 {
+    /// Start building a transaction spending inputs worth the given total amount, and sending the
+    /// change to K^j_s
+    pub fn new(change_address_spend_pubkey: AddressSpendPubkey, input_amount: Amount) -> Self {
+        Self {
+            recipients: Vec::new(),
+            change_address_spend_pubkey,
+            input_amount,
+            fee: 0,
+        }
+    }
+
+    /// Pay the given amount to an address. Recipients are indexed in the order they're added.
+    pub fn with_recipient(mut self, destination: CarrotDestinationV1, amount: Amount) -> Self {
+        self.recipients.push((destination, amount));
+        self
+    }
+
+    /// Set the fee paid by the transaction
+    pub fn with_fee(mut self, fee: Amount) -> Self {
+        self.fee = fee;
+        self
+    }
+
+    /// Recipients added so far, with their amounts
+    pub fn recipients(&self) -> &[(CarrotDestinationV1, Amount)] {
+        &self.recipients
+    }
+
+    /// Amount left for the change output: input amount - sum of payments - fee
+    pub fn change_amount(&self) -> Result<Amount> {
+        let total = self
+            .recipients
+            .iter()
+            .try_fold(self.fee, |total, (_, amount)| total.checked_add(*amount))
+            .ok_or(Error::new(ErrorKind::AmountOverflow))?;
+        self.input_amount
+            .checked_sub(total)
+            .ok_or(Error::new(ErrorKind::InsufficientFunds))
+    }
+
+    /// Finalize the output set of the transaction with the given first key image. Self-send
+    /// outputs are made internal with the view-balance secret if provided, and special with the
+    /// view-incoming key otherwise. Fresh anchors, and a dummy pid_enc if no recipient uses an
+    /// integrated address, are drawn from the RNG.
+    pub fn build<VB, VI, R>(
+        &self,
+        tx_first_key_image: KeyImage,
+        s_view_balance_dev: Option<&VB>,
+        k_view_dev: Option<&VI>,
+        rng: &mut R,
+    ) -> Result<CarrotTxProposalV1>
+    where
+        VB: ViewBalanceSecretDevice,
+        VI: ViewIncomingKeyDevice,
+        R: rand_core::CryptoRngCore,
+    {
+        let change_amount = self.change_amount()?;
+
+        let mut normal_payment_proposals: Vec<CarrotPaymentProposalV1> = self
+            .recipients
+            .iter()
+            .map(|(destination, amount)| CarrotPaymentProposalV1 {
+                destination: destination.clone(),
+                amount: *amount,
+                randomness: new_random(rng),
+            })
+            .collect();
+        let mut selfsend_payment_proposals = Vec::new();
+
+        // add the change output, or a dummy if the output set is otherwise complete
+        match get_additional_output_proposal(
+            normal_payment_proposals.len(),
+            selfsend_payment_proposals.len(),
+            change_amount,
+            false,
+            &self.change_address_spend_pubkey,
+            rng,
+        ) {
+            AdditionalOutputProposal::None => {}
+            AdditionalOutputProposal::Normal(proposal) => normal_payment_proposals.push(proposal),
+            AdditionalOutputProposal::Selfsend(proposal) => {
+                selfsend_payment_proposals.push(proposal)
+            }
+        }
+
+        let (output_enote_proposals, encrypted_payment_id, payment_proposal_order) =
+            get_output_enote_proposals_vec(
+                &normal_payment_proposals,
+                &selfsend_payment_proposals,
+                &Some(new_random(rng)),
+                s_view_balance_dev,
+                k_view_dev,
+                tx_first_key_image,
+            )?;
+
+        let num_recipients = self.recipients.len();
+        let recipient_indices = payment_proposal_order
+            .into_iter()
+            .map(|order| match order {
+                PaymentProposalOrder::Normal(i) if i < num_recipients => Some(i),
+                _ => None,
+            })
+            .collect();
+
+        Ok(CarrotTxProposalV1 {
+            output_enote_proposals,
+            encrypted_payment_id,
+            recipient_indices,
+        })
+    }
+}
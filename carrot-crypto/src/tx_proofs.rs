@@ -0,0 +1,453 @@
+use core::fmt::{Debug, Display};
+
+use curve25519_dalek::{EdwardsPoint, Scalar, edwards::CompressedEdwardsY};
+use group::GroupEncoding;
+
+use crate::as_crypto::{AsEdwardsPoint, AsMontgomeryPoint, AsScalar};
+use crate::device::{self, ViewIncomingKeyDevice};
+use crate::domain_separators;
+use crate::hash_functions::*;
+use crate::random::new_random;
+use crate::scan;
+use crate::transcript::*;
+use crate::*;
+
+/// Type of error encountered making or verifying a transaction proof
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Amount commitment doesn't open to the revealed amount and blinding factor
+    AmountCommitmentMismatch,
+    /// Sum of proven amounts overflowed
+    AmountOverflow,
+    /// Device threw an error
+    DeviceError,
+    /// Same enote was proven more than once
+    DuplicateEnote,
+    /// Enote ephemeral private key doesn't make the enote ephemeral pubkey for the destination
+    EphemeralKeyMismatch,
+    /// Proof, enote or destination contains invalid elliptic curve points
+    InvalidPoint,
+    /// Proof doesn't verify, so it's forged or proves another statement
+    InvalidProof,
+    /// Account can't open the one-time address of an enote it supposedly owns
+    OpeningFailed,
+    /// Enote doesn't pay the destination with the proven shared secret
+    ScanFailed(scan::ErrorKind),
+    /// View-incoming key doesn't make the destination's view pubkey
+    ViewKeyMismatch,
+}
+
+/// Error generated while making or verifying a transaction proof
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+}
+
+/// @TODO: real display
+impl Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(&self.kind, f)
+    }
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind) -> Self {
+        Self { kind }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+impl From<device::Error> for Error {
+    fn from(_: device::Error) -> Self {
+        Self::new(ErrorKind::DeviceError)
+    }
+}
+
+impl From<scan::Error> for Error {
+    fn from(error: scan::Error) -> Self {
+        Self::new(ErrorKind::ScanFailed(error.kind()))
+    }
+}
+
+impl core::error::Error for Error {}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[allow(non_snake_case)]
+pub(crate) fn decompress_torsion_free<E: AsEdwardsPoint>(P: &E) -> Result<EdwardsPoint> {
+    EdwardsPoint::from_bytes(&P.as_edwards_ref().0)
+        .into_option()
+        .filter(|P| P.is_torsion_free())
+        .ok_or(Error::new(ErrorKind::InvalidPoint))
+}
+
+// base point of a destination, where D_e = ConvertPointE(d_e B) and K^j_v = k_v B
+fn make_address_base(destination: &CarrotDestinationV1) -> Result<EdwardsPoint> {
+    if destination.is_subaddress {
+        // B = K^j_s
+        decompress_torsion_free(&destination.address_spend_pubkey)
+    } else {
+        // B = G
+        Ok(curve25519_dalek::constants::ED25519_BASEPOINT_POINT)
+    }
+}
+
+// K_e = ConvertPointM(D_e), taking the Edwards point with a clear sign bit
+fn recover_ephemeral_point(enote: &CarrotEnoteV1) -> Result<EdwardsPoint> {
+    enote
+        .enote_ephemeral_pubkey
+        .as_montgomery_ref()
+        .to_edwards(0)
+        .filter(|point| point.is_torsion_free())
+        .ok_or(Error::new(ErrorKind::InvalidPoint))
+}
+
+#[allow(non_snake_case)]
+fn make_out_proof_challenge(
+    enote: &CarrotEnoteV1,
+    destination: &CarrotDestinationV1,
+    message: &[u8],
+    S: &CompressedEdwardsY,
+    R_e: &CompressedEdwardsY,
+    R_v: &CompressedEdwardsY,
+) -> Scalar {
+    // c = H_n[H_32(msg)](K_o, D_e, K^j_s, K^j_v, S, R_e, R_v)
+    let message_hash = derive_bytes_32(message, &[]);
+    let transcript = make_carrot_transcript!(domain_separators::OUT_PROOF_CHALLENGE,
+        OutputPubkey : &enote.onetime_address, EnoteEphemeralPubkey : &enote.enote_ephemeral_pubkey,
+        AddressSpendPubkey : &destination.address_spend_pubkey,
+        AddressViewPubkey : &destination.address_view_pubkey,
+        CompressedEdwardsY : S, CompressedEdwardsY : R_e, CompressedEdwardsY : R_v);
+    derive_scalar(&transcript, &message_hash)
+}
+
+type MessageHash = [u8; 32];
+
+#[allow(non_snake_case)]
+fn make_dleq_challenge(
+    message: &MessageHash,
+    B_1: &CompressedEdwardsY,
+    P_1: &CompressedEdwardsY,
+    B_2: &CompressedEdwardsY,
+    P_2: &CompressedEdwardsY,
+    R_1: &CompressedEdwardsY,
+    R_2: &CompressedEdwardsY,
+) -> Scalar {
+    // c = H_n[m](B_1, P_1, B_2, P_2, R_1, R_2)
+    let transcript = make_carrot_transcript!(domain_separators::DLEQ_CHALLENGE,
+        CompressedEdwardsY : B_1, CompressedEdwardsY : P_1, CompressedEdwardsY : B_2,
+        CompressedEdwardsY : P_2, CompressedEdwardsY : R_1, CompressedEdwardsY : R_2);
+    derive_scalar(&transcript, message)
+}
+
+/// DLEQ proof (c, r) that x is the discrete log of both x B_1 and x B_2, bound to a message, with
+/// a deterministic nonce so that devices don't need an RNG
+#[allow(non_snake_case)]
+pub(crate) fn make_dleq_proof(
+    x: &Scalar,
+    B_1: &EdwardsPoint,
+    B_2: &EdwardsPoint,
+    message: &MessageHash,
+) -> (Scalar, Scalar) {
+    let B_1_compressed = B_1.compress();
+    let B_2_compressed = B_2.compress();
+
+    // k = H_n[x](m, B_1, B_2)
+    let transcript = make_carrot_transcript!(domain_separators::DLEQ_NONCE,
+        MessageHash : message, CompressedEdwardsY : &B_1_compressed,
+        CompressedEdwardsY : &B_2_compressed);
+    let k = derive_scalar(&transcript, x.as_bytes());
+
+    // r = k - c x
+    let challenge = make_dleq_challenge(
+        message,
+        &B_1_compressed,
+        &(x * B_1).compress(),
+        &B_2_compressed,
+        &(x * B_2).compress(),
+        &(k * B_1).compress(),
+        &(k * B_2).compress(),
+    );
+    (challenge, k - challenge * x)
+}
+
+#[allow(non_snake_case)]
+fn verify_dleq_proof(
+    B_1: &EdwardsPoint,
+    P_1: &EdwardsPoint,
+    B_2: &EdwardsPoint,
+    P_2: &EdwardsPoint,
+    message: &MessageHash,
+    challenge: &Scalar,
+    response: &Scalar,
+) -> bool {
+    // R_1 = r B_1 + c P_1, R_2 = r B_2 + c P_2
+    let R_1 = response * B_1 + challenge * P_1;
+    let R_2 = response * B_2 + challenge * P_2;
+    *challenge
+        == make_dleq_challenge(
+            message,
+            &B_1.compress(),
+            &P_1.compress(),
+            &B_2.compress(),
+            &P_2.compress(),
+            &R_1.compress(),
+            &R_2.compress(),
+        )
+}
+
+// S || c || r serialization of proofs of a shared secret point
+macro_rules! impl_shared_secret_proof_bytes {
+    ($proof:ty) => {
+        impl $proof {
+            /// Serialize as S || c || r
+            pub fn to_bytes(&self) -> [u8; 96] {
+                let mut bytes = [0u8; 96];
+                bytes[..32].copy_from_slice(self.shared_secret_point.as_bytes());
+                bytes[32..64].copy_from_slice(self.challenge.as_bytes());
+                bytes[64..].copy_from_slice(self.response.as_bytes());
+                bytes
+            }
+
+            /// Deserialize from S || c || r, or None if a scalar isn't canonical
+            pub fn from_bytes(bytes: &[u8; 96]) -> Option<Self> {
+                let scalar_from_slice = |slice: &[u8]| {
+                    Scalar::from_canonical_bytes(slice.try_into().expect("32-byte slice"))
+                        .into_option()
+                };
+                Some(Self {
+                    shared_secret_point: CompressedEdwardsY(
+                        bytes[..32].try_into().expect("32-byte slice"),
+                    ),
+                    challenge: scalar_from_slice(&bytes[32..64])?,
+                    response: scalar_from_slice(&bytes[64..])?,
+                })
+            }
+        }
+    };
+}
+
+/// Outgoing payment proof: the sender of an enote proves that its ECDH exchange was made with a
+/// destination's view pubkey, which lets anyone holding the destination and the enote recover the
+/// payment. This is a DLEQ proof that the discrete log of K_e = ConvertPointM(D_e) to the base
+/// B (G for main addresses, K^j_s for subaddresses) equals that of S to the base K^j_v.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CarrotOutProofV1 {
+    /// S = d_e K^j_v, Edwards form of the uncontextualized shared secret s_sr
+    pub shared_secret_point: CompressedEdwardsY,
+    /// c
+    pub challenge: Scalar,
+    /// r
+    pub response: Scalar,
+}
+
+impl CarrotOutProofV1 {
+    /// Prove that an enote was sent to a destination with the enote ephemeral private key d_e,
+    /// binding the proof to a message. A sender who kept `anchor_norm` can rederive d_e with
+    /// [`EnoteEphemeralKey::derive`].
+    #[allow(non_snake_case)]
+    pub fn prove<R>(
+        enote: &CarrotEnoteV1,
+        destination: &CarrotDestinationV1,
+        enote_ephemeral_privkey: &EnoteEphemeralKey,
+        message: &[u8],
+        rng: &mut R,
+    ) -> Result<Self>
+    where
+        R: rand_core::CryptoRngCore + ?Sized,
+    {
+        let B = make_address_base(destination)?;
+        let K_v = decompress_torsion_free(&destination.address_view_pubkey)?;
+
+        // K_e = d_e B, where D_e = ConvertPointE(K_e)
+        let mut d_e = *enote_ephemeral_privkey.as_scalar_ref();
+        let mut K_e = d_e * B;
+        if K_e.to_montgomery() != *enote.enote_ephemeral_pubkey.as_montgomery_ref() {
+            return Err(Error::new(ErrorKind::EphemeralKeyMismatch));
+        }
+
+        // D_e only fixes K_e up to its sign, so prove with whichever of +/-d_e makes the K_e with a
+        // clear sign bit, which is the one the verifier recovers
+        if K_e.compress().0[31] >> 7 == 1 {
+            d_e = -d_e;
+            K_e = -K_e;
+        }
+        debug_assert_eq!(0, K_e.compress().0[31] >> 7);
+
+        // S = d_e K^j_v
+        let S = (d_e * K_v).compress();
+
+        // R_e = k B, R_v = k K^j_v
+        let k: Scalar = new_random(rng);
+        let R_e = (k * B).compress();
+        let R_v = (k * K_v).compress();
+
+        // r = k - c d_e
+        let challenge = make_out_proof_challenge(enote, destination, message, &S, &R_e, &R_v);
+        let response = k - challenge * d_e;
+
+        Ok(Self {
+            shared_secret_point: S,
+            challenge,
+            response,
+        })
+    }
+
+    /// Verify that an enote was sent to a destination, and recover the payment with the proven
+    /// shared secret. Only normal payments, not self-sends, can be proven this way.
+    #[allow(non_snake_case)]
+    pub fn verify(
+        &self,
+        enote: &CarrotEnoteV1,
+        encrypted_payment_id: Option<&EncryptedPaymentId>,
+        destination: &CarrotDestinationV1,
+        message: &[u8],
+    ) -> Result<CarrotExternalEnoteRecordV1> {
+        let B = make_address_base(destination)?;
+        let K_v = decompress_torsion_free(&destination.address_view_pubkey)?;
+
+        let K_e = recover_ephemeral_point(enote)?;
+        let S = decompress_torsion_free(&self.shared_secret_point)?;
+
+        // R_e = r B + c K_e, R_v = r K^j_v + c S
+        let R_e = (self.response * B + self.challenge * K_e).compress();
+        let R_v = (self.response * K_v + self.challenge * S).compress();
+        let challenge = make_out_proof_challenge(
+            enote,
+            destination,
+            message,
+            &self.shared_secret_point,
+            &R_e,
+            &R_v,
+        );
+        if challenge != self.challenge {
+            return Err(Error::new(ErrorKind::InvalidProof));
+        }
+
+        // s_sr = ConvertPointE(S)
+        let s_sender_receiver_unctx =
+            MontgomeryECDH::derive_from_edwards(&self.shared_secret_point)
+                .ok_or(Error::new(ErrorKind::InvalidPoint))?;
+
+        Ok(
+            scan::try_scan_carrot_enote_external_sender_with_shared_secret(
+                enote,
+                encrypted_payment_id,
+                destination,
+                &s_sender_receiver_unctx,
+                true,
+            )?,
+        )
+    }
+}
+
+impl_shared_secret_proof_bytes!(CarrotOutProofV1);
+
+/// Incoming payment proof: the recipient of an enote proves, without revealing k_v, that
+/// S = k_v K_e where K_e = ConvertPointM(D_e), i.e. that S is the ECDH exchange with the view key
+/// of a destination's view pubkey K^j_v = k_v B. This lets anyone holding the destination and the
+/// enote check ownership and recover the amount, and nothing else about the recipient's wallet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CarrotInProofV1 {
+    /// S = k_v K_e, Edwards form of the uncontextualized shared secret s_sr
+    pub shared_secret_point: CompressedEdwardsY,
+    /// c
+    pub challenge: Scalar,
+    /// r
+    pub response: Scalar,
+}
+
+fn make_in_proof_message(
+    enote: &CarrotEnoteV1,
+    destination: &CarrotDestinationV1,
+    message: &[u8],
+) -> MessageHash {
+    // m = H_32[H_32(msg)](K_o, D_e, K^j_s)
+    let message_hash = derive_bytes_32(message, &[]);
+    let transcript = make_carrot_transcript!(domain_separators::IN_PROOF_MESSAGE,
+        OutputPubkey : &enote.onetime_address, EnoteEphemeralPubkey : &enote.enote_ephemeral_pubkey,
+        AddressSpendPubkey : &destination.address_spend_pubkey);
+    derive_bytes_32(&transcript, &message_hash)
+}
+
+impl CarrotInProofV1 {
+    /// Prove that an enote was received at one of our destinations, binding the proof to a
+    /// message. The view-incoming key never leaves the device.
+    #[allow(non_snake_case)]
+    pub fn prove<VI: ViewIncomingKeyDevice>(
+        enote: &CarrotEnoteV1,
+        destination: &CarrotDestinationV1,
+        k_view_dev: &VI,
+        message: &[u8],
+    ) -> Result<Self> {
+        let B = make_address_base(destination)?;
+        let K_e = recover_ephemeral_point(enote)?;
+
+        // K^j_v = k_v B, else the destination isn't ours
+        let K_v = k_view_dev.view_key_scalar_mult_ed25519(&B)?;
+        if K_v.compress() != *destination.address_view_pubkey.as_edwards_ref() {
+            return Err(Error::new(ErrorKind::ViewKeyMismatch));
+        }
+
+        // S = k_v K_e
+        let S = k_view_dev.view_key_scalar_mult_ed25519(&K_e)?;
+        let message = make_in_proof_message(enote, destination, message);
+        let (challenge, response) = k_view_dev.make_view_key_dleq_proof(&B, &K_e, &message)?;
+
+        Ok(Self {
+            shared_secret_point: S.compress(),
+            challenge,
+            response,
+        })
+    }
+
+    /// Verify that an enote was received at a destination, and recover the payment with the
+    /// proven shared secret. Only normal payments, not self-sends, can be proven this way.
+    #[allow(non_snake_case)]
+    pub fn verify(
+        &self,
+        enote: &CarrotEnoteV1,
+        encrypted_payment_id: Option<&EncryptedPaymentId>,
+        destination: &CarrotDestinationV1,
+        message: &[u8],
+    ) -> Result<CarrotExternalEnoteRecordV1> {
+        let B = make_address_base(destination)?;
+        let K_v = decompress_torsion_free(&destination.address_view_pubkey)?;
+        let K_e = recover_ephemeral_point(enote)?;
+        let S = decompress_torsion_free(&self.shared_secret_point)?;
+
+        // log_B(K^j_v) = log_K_e(S)
+        let message = make_in_proof_message(enote, destination, message);
+        if !verify_dleq_proof(
+            &B,
+            &K_v,
+            &K_e,
+            &S,
+            &message,
+            &self.challenge,
+            &self.response,
+        ) {
+            return Err(Error::new(ErrorKind::InvalidProof));
+        }
+
+        // s_sr = ConvertPointE(S)
+        let s_sender_receiver_unctx =
+            MontgomeryECDH::derive_from_edwards(&self.shared_secret_point)
+                .ok_or(Error::new(ErrorKind::InvalidPoint))?;
+
+        Ok(
+            scan::try_scan_carrot_enote_external_sender_with_shared_secret(
+                enote,
+                encrypted_payment_id,
+                destination,
+                &s_sender_receiver_unctx,
+                true,
+            )?,
+        )
+    }
+}
+
+impl_shared_secret_proof_bytes!(CarrotInProofV1);
@@ -0,0 +1,596 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Display, Write};
+
+use crate::address::{self, Network};
+use crate::consts::PAYMENT_ID_BYTES;
+use crate::*;
+
+const URI_SCHEME: &str = "monero:";
+const ATOMIC_UNITS_DECIMALS: usize = 12;
+const ATOMIC_UNITS_PER_XMR: Amount = 1_000_000_000_000;
+
+/// Type of error encountered parsing or building payment URIs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Address couldn't be encoded or decoded
+    BadAddress(address::ErrorKind),
+    /// Amount isn't a decimal XMR amount representable in atomic units
+    BadAmount,
+    /// Payment ID isn't 16 hex characters
+    BadPaymentId,
+    /// Parameter contains a bad percent-encoded sequence, or isn't UTF-8 once decoded
+    BadPercentEncoding,
+    /// Same parameter appears more than once
+    DuplicateParameter,
+    /// Number of amounts or recipient names doesn't match the number of addresses
+    MismatchedRecipientCount,
+    /// URI doesn't contain any addresses
+    MissingAddress,
+    /// Addresses belong to different networks
+    NetworkMismatch,
+    /// URI doesn't start with the "monero:" scheme
+    UnknownScheme,
+    /// More than one integrated address, or a separate payment ID without a main address to go with
+    WrongAddressType,
+}
+
+/// Error generated while parsing or building payment URIs
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+}
+
+/// @TODO: real display
+impl Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(&self.kind, f)
+    }
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind) -> Self {
+        Self { kind }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+impl From<address::Error> for Error {
+    fn from(error: address::Error) -> Self {
+        Self::new(ErrorKind::BadAddress(error.kind()))
+    }
+}
+
+impl core::error::Error for Error {}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// Recipient of a payment URI
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CarrotPaymentUriRecipientV1 {
+    /// address to pay
+    pub destination: CarrotDestinationV1,
+    /// tx_amount, in atomic units
+    pub amount: Option<Amount>,
+    /// recipient_name
+    pub recipient_name: Option<String>,
+}
+
+/// Payment request as a "monero:" URI: monero:<address>[;<address>...]?tx_amount=...
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CarrotPaymentUriV1 {
+    /// network of all the recipients' addresses
+    pub network: Network,
+    /// recipients, in URI order
+    pub recipients: Vec<CarrotPaymentUriRecipientV1>,
+    /// tx_payment_id, for a recipient with a main address but no integrated address
+    pub payment_id: Option<PaymentId>,
+    /// tx_description
+    pub tx_description: Option<String>,
+}
+
+// decimal XMR amount, with trailing fractional zeros trimmed
+fn format_amount(amount: Amount, out: &mut String) {
+    let whole = amount / ATOMIC_UNITS_PER_XMR;
+    let fraction = amount % ATOMIC_UNITS_PER_XMR;
+    write!(out, "{whole}").expect("writing to a String can't fail");
+    if fraction != 0 {
+        let mut fraction_digits = String::new();
+        write!(
+            fraction_digits,
+            "{fraction:0width$}",
+            width = ATOMIC_UNITS_DECIMALS
+        )
+        .expect("writing to a String can't fail");
+        out.push('.');
+        out.push_str(fraction_digits.trim_end_matches('0'));
+    }
+}
+
+// decimal XMR amount to atomic units
+fn parse_amount(s: &str) -> Result<Amount> {
+    let bad_amount = || Error::new(ErrorKind::BadAmount);
+    let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+    let is_digits = |digits: &str| digits.bytes().all(|c| c.is_ascii_digit());
+    if whole.is_empty()
+        || !is_digits(whole)
+        || !is_digits(fraction)
+        || fraction.len() > ATOMIC_UNITS_DECIMALS
+        || (s.contains('.') && fraction.is_empty())
+    {
+        return Err(bad_amount());
+    }
+
+    // a = whole * 10^12 + fraction * 10^(12 - len(fraction))
+    let whole: Amount = whole.parse().map_err(|_| bad_amount())?;
+    let fraction_scale = 10u64.pow((ATOMIC_UNITS_DECIMALS - fraction.len()) as u32);
+    let fraction: Amount = if fraction.is_empty() {
+        0
+    } else {
+        fraction.parse::<Amount>().map_err(|_| bad_amount())? * fraction_scale
+    };
+    whole
+        .checked_mul(ATOMIC_UNITS_PER_XMR)
+        .and_then(|whole| whole.checked_add(fraction))
+        .ok_or(bad_amount())
+}
+
+fn format_payment_id(payment_id: &PaymentId, out: &mut String) {
+    for byte in payment_id.as_bytes() {
+        write!(out, "{byte:02x}").expect("writing to a String can't fail");
+    }
+}
+
+// two hex digits, where u8::from_str_radix() alone would also accept a sign like "+f"
+fn parse_hex_byte(hex: [u8; 2]) -> Option<u8> {
+    if !hex.iter().all(u8::is_ascii_hexdigit) {
+        return None;
+    }
+    u8::from_str_radix(core::str::from_utf8(&hex).ok()?, 16).ok()
+}
+
+fn parse_payment_id(s: &str) -> Result<PaymentId> {
+    let bad_payment_id = || Error::new(ErrorKind::BadPaymentId);
+    if s.len() != 2 * PAYMENT_ID_BYTES {
+        return Err(bad_payment_id());
+    }
+    let mut payment_id = [0u8; PAYMENT_ID_BYTES];
+    for (byte, hex) in payment_id.iter_mut().zip(s.as_bytes().chunks_exact(2)) {
+        *byte = parse_hex_byte([hex[0], hex[1]]).ok_or(bad_payment_id())?;
+    }
+    Ok(PaymentId::from(payment_id))
+}
+
+// percent-encode everything but RFC 3986 unreserved characters
+fn percent_encode(s: &str, out: &mut String) {
+    for byte in s.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            out.push(byte as char);
+        } else {
+            write!(out, "%{byte:02X}").expect("writing to a String can't fail");
+        }
+    }
+}
+
+fn percent_decode(s: &str) -> Result<String> {
+    let bad_encoding = || Error::new(ErrorKind::BadPercentEncoding);
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut chars = s.bytes();
+    while let Some(byte) = chars.next() {
+        if byte == b'%' {
+            let hex = [
+                chars.next().ok_or(bad_encoding())?,
+                chars.next().ok_or(bad_encoding())?,
+            ];
+            bytes.push(parse_hex_byte(hex).ok_or(bad_encoding())?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| bad_encoding())
+}
+
+impl CarrotPaymentUriV1 {
+    // assert there is a max of 1 integrated address, counting a separate payment ID as one, and
+    // that a separate payment ID has a main address to go with
+    fn check_integrated_addresses(&self) -> Result<()> {
+        let num_integrated = self
+            .recipients
+            .iter()
+            .filter(|recipient| recipient.destination.is_integrated())
+            .count()
+            + self.payment_id.is_some() as usize;
+        if num_integrated > 1 {
+            return Err(Error::new(ErrorKind::WrongAddressType));
+        }
+        let has_main_address = self.recipients.iter().any(|recipient| {
+            !recipient.destination.is_subaddress && !recipient.destination.is_integrated()
+        });
+        if self.payment_id.is_some() && !has_main_address {
+            return Err(Error::new(ErrorKind::WrongAddressType));
+        }
+        Ok(())
+    }
+
+    /// Build the "monero:" URI string of this payment request
+    pub fn to_uri_string(&self) -> Result<String> {
+        // 1. sanity checks
+        if self.recipients.is_empty() {
+            return Err(Error::new(ErrorKind::MissingAddress));
+        }
+        self.check_integrated_addresses()?;
+
+        // 2. monero:<address>[;<address>...]
+        let mut uri = String::from(URI_SCHEME);
+        for (i, recipient) in self.recipients.iter().enumerate() {
+            if i != 0 {
+                uri.push(';');
+            }
+            uri.push_str(
+                recipient
+                    .destination
+                    .to_address_string(self.network)?
+                    .as_str(),
+            );
+        }
+
+        // 3. ?<parameter>=<value>[&<parameter>=<value>...], where per-recipient values are joined
+        // by ';', and are all present if any is, with an empty value for a missing amount
+        let mut num_parameters = 0;
+        let mut start_parameter = |uri: &mut String, name: &str| {
+            uri.push(if num_parameters == 0 { '?' } else { '&' });
+            uri.push_str(name);
+            uri.push('=');
+            num_parameters += 1;
+        };
+        if self.recipients.iter().any(|r| r.amount.is_some()) {
+            start_parameter(&mut uri, "tx_amount");
+            for (i, recipient) in self.recipients.iter().enumerate() {
+                if i != 0 {
+                    uri.push(';');
+                }
+                if let Some(amount) = recipient.amount {
+                    format_amount(amount, &mut uri);
+                }
+            }
+        }
+        if let Some(payment_id) = &self.payment_id {
+            start_parameter(&mut uri, "tx_payment_id");
+            format_payment_id(payment_id, &mut uri);
+        }
+        if self.recipients.iter().any(|r| r.recipient_name.is_some()) {
+            start_parameter(&mut uri, "recipient_name");
+            for (i, recipient) in self.recipients.iter().enumerate() {
+                if i != 0 {
+                    uri.push(';');
+                }
+                percent_encode(recipient.recipient_name.as_deref().unwrap_or(""), &mut uri);
+            }
+        }
+        if let Some(tx_description) = &self.tx_description {
+            start_parameter(&mut uri, "tx_description");
+            percent_encode(tx_description, &mut uri);
+        }
+
+        Ok(uri)
+    }
+
+    /// Parse a "monero:" URI string into a payment request. Unknown parameters are ignored.
+    pub fn from_uri_string(uri: &str) -> Result<Self> {
+        // 1. strip scheme, and split addresses from parameters
+        let uri = uri
+            .strip_prefix(URI_SCHEME)
+            .ok_or(Error::new(ErrorKind::UnknownScheme))?;
+        let (addresses, parameters) = uri.split_once('?').unwrap_or((uri, ""));
+        if addresses.is_empty() {
+            return Err(Error::new(ErrorKind::MissingAddress));
+        }
+
+        // 2. decode addresses, which must all be on the same network
+        let mut network = None;
+        let mut recipients = Vec::new();
+        for address in addresses.split(';') {
+            let (destination, address_network) = CarrotDestinationV1::from_address_string(address)?;
+            if *network.get_or_insert(address_network) != address_network {
+                return Err(Error::new(ErrorKind::NetworkMismatch));
+            }
+            recipients.push(CarrotPaymentUriRecipientV1 {
+                destination,
+                amount: None,
+                recipient_name: None,
+            });
+        }
+        let network = network.ok_or(Error::new(ErrorKind::MissingAddress))?;
+
+        // 3. parse parameters, where per-recipient values must match the number of addresses
+        let mut payment_uri = Self {
+            network,
+            recipients,
+            payment_id: None,
+            tx_description: None,
+        };
+        let mut seen_amounts = false;
+        let mut seen_names = false;
+        let num_recipients = payment_uri.recipients.len();
+        let split_per_recipient = |value: &str| -> Result<Vec<String>> {
+            let values = value
+                .split(';')
+                .map(percent_decode)
+                .collect::<Result<Vec<String>>>()?;
+            if values.len() != num_recipients {
+                return Err(Error::new(ErrorKind::MismatchedRecipientCount));
+            }
+            Ok(values)
+        };
+        let check_duplicate = |seen: bool| match seen {
+            true => Err(Error::new(ErrorKind::DuplicateParameter)),
+            false => Ok(()),
+        };
+        for parameter in parameters.split('&').filter(|p| !p.is_empty()) {
+            let (name, value) = parameter.split_once('=').unwrap_or((parameter, ""));
+            match name {
+                "tx_amount" => {
+                    check_duplicate(seen_amounts)?;
+                    seen_amounts = true;
+                    let amounts = split_per_recipient(value)?;
+                    for (recipient, amount) in payment_uri.recipients.iter_mut().zip(amounts) {
+                        recipient.amount = match amount.is_empty() {
+                            true => None,
+                            false => Some(parse_amount(&amount)?),
+                        };
+                    }
+                }
+                "tx_payment_id" => {
+                    check_duplicate(payment_uri.payment_id.is_some())?;
+                    payment_uri.payment_id = Some(parse_payment_id(&percent_decode(value)?)?);
+                }
+                "recipient_name" => {
+                    check_duplicate(seen_names)?;
+                    seen_names = true;
+                    let names = split_per_recipient(value)?;
+                    for (recipient, name) in payment_uri.recipients.iter_mut().zip(names) {
+                        recipient.recipient_name = (!name.is_empty()).then_some(name);
+                    }
+                }
+                "tx_description" => {
+                    check_duplicate(payment_uri.tx_description.is_some())?;
+                    payment_uri.tx_description = Some(percent_decode(value)?);
+                }
+                _ => {}
+            }
+        }
+
+        // 4. integrated address sanity checks
+        payment_uri.check_integrated_addresses()?;
+
+        Ok(payment_uri)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use crate::random::Random;
+    use crate::uri::*;
+
+    const MONERO_GENERAL_FUND_SUBADDRESS: &str = "888tNkZrPN6JsEgekjMnABU4TBzc2Dt29EPAvkRxbANsAnjyPbb3iQ1YBRk1UXcdRsiKc9dhwMVgN5S9cQUiyoogDavup3H";
+
+    fn make_destination(is_subaddress: bool, is_integrated: bool) -> CarrotDestinationV1 {
+        CarrotDestinationV1::new_random_with_params(
+            &mut rand_core::OsRng,
+            (is_subaddress, is_integrated),
+        )
+    }
+
+    fn make_recipient(
+        destination: CarrotDestinationV1,
+        amount: Option<Amount>,
+        recipient_name: Option<&str>,
+    ) -> CarrotPaymentUriRecipientV1 {
+        CarrotPaymentUriRecipientV1 {
+            destination,
+            amount,
+            recipient_name: recipient_name.map(|name| name.to_string()),
+        }
+    }
+
+    #[test]
+    fn amount_round_trip() {
+        for (amount, formatted) in [
+            (0, "0"),
+            (1, "0.000000000001"),
+            (1_500_000_000_000, "1.5"),
+            (2_000_000_000_000, "2"),
+            (Amount::MAX, "18446744.073709551615"),
+        ] {
+            let mut s = String::new();
+            format_amount(amount, &mut s);
+            assert_eq!(formatted, s);
+            assert_eq!(amount, parse_amount(formatted).unwrap());
+        }
+        assert_eq!(1_230_000_000_000, parse_amount("1.230").unwrap());
+        for bad_amount in [
+            "",
+            ".5",
+            "1.",
+            "1.0000000000001",
+            "-1",
+            "1e3",
+            "18446744.073709551616",
+        ] {
+            assert_eq!(
+                ErrorKind::BadAmount,
+                parse_amount(bad_amount).unwrap_err().kind()
+            );
+        }
+    }
+
+    #[test]
+    fn parse_known_uri() {
+        let uri = "monero:".to_string()
+            + MONERO_GENERAL_FUND_SUBADDRESS
+            + "?tx_amount=0.25&recipient_name=Monero%20General%20Fund&tx_description=Donation"
+            + "&unknown_parameter=1";
+        let payment_uri = CarrotPaymentUriV1::from_uri_string(&uri).unwrap();
+        assert_eq!(Network::Mainnet, payment_uri.network);
+        assert_eq!(1, payment_uri.recipients.len());
+        assert!(payment_uri.recipients[0].destination.is_subaddress);
+        assert_eq!(Some(250_000_000_000), payment_uri.recipients[0].amount);
+        assert_eq!(
+            Some("Monero General Fund"),
+            payment_uri.recipients[0].recipient_name.as_deref()
+        );
+        assert_eq!(Some("Donation"), payment_uri.tx_description.as_deref());
+        assert_eq!(None, payment_uri.payment_id);
+
+        // bare address
+        let uri = "monero:".to_string() + MONERO_GENERAL_FUND_SUBADDRESS;
+        let payment_uri = CarrotPaymentUriV1::from_uri_string(&uri).unwrap();
+        assert_eq!(None, payment_uri.recipients[0].amount);
+        assert_eq!(uri, payment_uri.to_uri_string().unwrap());
+    }
+
+    #[test]
+    fn uri_round_trip() {
+        let payment_uri = CarrotPaymentUriV1 {
+            network: Network::Stagenet,
+            recipients: vec![
+                make_recipient(make_destination(false, true), Some(1), Some("Alice & Bob")),
+                make_recipient(make_destination(true, false), Some(3_000_000_000_000), None),
+                make_recipient(make_destination(false, false), None, Some("Café;Carol")),
+            ],
+            payment_id: None,
+            tx_description: Some("order #42: 1x t-shirt, 100% cotton".to_string()),
+        };
+        let uri = payment_uri.to_uri_string().unwrap();
+        assert!(uri.starts_with("monero:5"));
+        assert!(uri.contains("?tx_amount=0.000000000001;3;&recipient_name="));
+        assert_eq!(
+            payment_uri,
+            CarrotPaymentUriV1::from_uri_string(&uri).unwrap()
+        );
+
+        // separate payment ID for a main address
+        let payment_uri = CarrotPaymentUriV1 {
+            network: Network::Mainnet,
+            recipients: vec![make_recipient(
+                make_destination(false, false),
+                Some(7),
+                None,
+            )],
+            payment_id: Some(PaymentId::from([0xab, 0, 1, 2, 3, 4, 5, 0xff])),
+            tx_description: None,
+        };
+        let uri = payment_uri.to_uri_string().unwrap();
+        assert!(uri.ends_with("?tx_amount=0.000000000007&tx_payment_id=ab000102030405ff"));
+        assert_eq!(
+            payment_uri,
+            CarrotPaymentUriV1::from_uri_string(&uri).unwrap()
+        );
+    }
+
+    #[test]
+    fn reject_bad_uris() {
+        let address = |destination: &CarrotDestinationV1, network| {
+            destination
+                .to_address_string(network)
+                .unwrap()
+                .as_str()
+                .to_string()
+        };
+        let main_address = address(&make_destination(false, false), Network::Mainnet);
+        let integrated_address = address(&make_destination(false, true), Network::Mainnet);
+        let subaddress = address(&make_destination(true, false), Network::Mainnet);
+        let testnet_address = address(&make_destination(true, false), Network::Testnet);
+        let parse_error = |uri: String| {
+            CarrotPaymentUriV1::from_uri_string(&uri)
+                .unwrap_err()
+                .kind()
+        };
+
+        assert_eq!(
+            ErrorKind::UnknownScheme,
+            parse_error("bitcoin:".to_string() + &main_address)
+        );
+        assert_eq!(
+            ErrorKind::MissingAddress,
+            parse_error("monero:?tx_amount=1".to_string())
+        );
+        assert_eq!(
+            ErrorKind::BadAddress(address::ErrorKind::BadChecksum),
+            parse_error("monero:".to_string() + &main_address[..94] + "1")
+        );
+        assert_eq!(
+            ErrorKind::NetworkMismatch,
+            parse_error("monero:".to_string() + &main_address + ";" + &testnet_address)
+        );
+        assert_eq!(
+            ErrorKind::MismatchedRecipientCount,
+            parse_error(
+                "monero:".to_string() + &main_address + ";" + &main_address + "?tx_amount=1"
+            )
+        );
+        assert_eq!(
+            ErrorKind::DuplicateParameter,
+            parse_error("monero:".to_string() + &main_address + "?tx_amount=1&tx_amount=2")
+        );
+        assert_eq!(
+            ErrorKind::BadPaymentId,
+            parse_error("monero:".to_string() + &main_address + "?tx_payment_id=0123456789abcdeg")
+        );
+        assert_eq!(
+            ErrorKind::BadPaymentId,
+            parse_error("monero:".to_string() + &main_address + "?tx_payment_id=+f+f+f+f+f+f+f+f")
+        );
+        assert_eq!(
+            ErrorKind::BadPercentEncoding,
+            parse_error("monero:".to_string() + &main_address + "?tx_description=100%")
+        );
+        assert_eq!(
+            ErrorKind::BadPercentEncoding,
+            parse_error("monero:".to_string() + &main_address + "?tx_description=%+f")
+        );
+
+        // at most one integrated address, counting a separate payment ID as one, which needs a
+        // main address
+        assert_eq!(
+            ErrorKind::WrongAddressType,
+            parse_error("monero:".to_string() + &integrated_address + ";" + &integrated_address)
+        );
+        assert_eq!(
+            ErrorKind::WrongAddressType,
+            parse_error(
+                "monero:".to_string() + &integrated_address + "?tx_payment_id=0123456789abcdef"
+            )
+        );
+        assert_eq!(
+            ErrorKind::WrongAddressType,
+            parse_error("monero:".to_string() + &subaddress + "?tx_payment_id=0123456789abcdef")
+        );
+        assert!(
+            CarrotPaymentUriV1::from_uri_string(
+                &("monero:".to_string()
+                    + &subaddress
+                    + ";"
+                    + &main_address
+                    + "?tx_payment_id=0123456789abcdef")
+            )
+            .is_ok()
+        );
+        let payment_uri = CarrotPaymentUriV1 {
+            network: Network::Mainnet,
+            recipients: vec![make_recipient(make_destination(false, true), None, None)],
+            payment_id: Some(PaymentId::from([1; PAYMENT_ID_BYTES])),
+            tx_description: None,
+        };
+        assert_eq!(
+            ErrorKind::WrongAddressType,
+            payment_uri.to_uri_string().unwrap_err().kind()
+        );
+    }
+}
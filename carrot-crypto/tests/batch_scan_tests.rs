@@ -0,0 +1,160 @@
+#![cfg(feature = "alloc")]
+
+mod common;
+use crate::common::keys::make_subaddress_table;
+use crate::common::random::*;
+
+use carrot_crypto::scan_batch::*;
+use carrot_crypto::*;
+
+type BatchEnote = (CarrotEnoteV1, Option<EncryptedPaymentId>);
+
+fn make_payment(destination: CarrotDestinationV1) -> BatchEnote {
+    let proposal = payments::CarrotPaymentProposalV1 {
+        destination,
+        amount: gen_random(),
+        randomness: gen_random(),
+    };
+    let (enote_proposal, encrypted_payment_id) = proposal
+        .get_normal_output_proposal(gen_random())
+        .expect("get_normal_output_proposal");
+    (enote_proposal.enote, Some(encrypted_payment_id))
+}
+
+// bob receives to (0, 0), (0, 2), (0, 4) and (0, 6), where (0, 4) is only in the lookahead window
+// after receiving to (0, 2), and (0, 6) only after receiving to (0, 4). the enote to (0, 6) comes
+// first in the batch, so it can only be found by retrying once the window has grown.
+fn make_batch(bob: &CarrotAccount) -> (Vec<BatchEnote>, Vec<(usize, SubaddressIndex)>) {
+    let alice: CarrotAccount = gen_random();
+    let destination = |minor: u32| match minor {
+        0 => bob.main_address(),
+        _ => bob.subaddress(0, minor).expect("subaddress"),
+    };
+
+    let batch = vec![
+        make_payment(alice.main_address()),
+        make_payment(destination(6)),
+        make_payment(destination(0)),
+        make_payment(alice.subaddress(0, 1).expect("subaddress")),
+        make_payment(destination(2)),
+        make_payment(destination(4)),
+        make_payment(alice.main_address()),
+    ];
+    let expected_owned = [1, 2, 4, 5]
+        .into_iter()
+        .zip([6, 0, 2, 4])
+        .map(|(batch_index, minor)| (batch_index, SubaddressIndex { major: 0, minor }))
+        .collect();
+
+    (batch, expected_owned)
+}
+
+fn check_batch_scan_result(
+    scan_result: &CarrotBatchScanResultV1,
+    expected_owned: &[(usize, SubaddressIndex)],
+) {
+    assert!(scan_result.janus_failures.is_empty());
+    assert_eq!(expected_owned.len(), scan_result.owned_enotes.len());
+    for (owned_enote, (batch_index, subaddress_index)) in
+        scan_result.owned_enotes.iter().zip(expected_owned)
+    {
+        assert_eq!(*batch_index, owned_enote.batch_index);
+        assert_eq!(
+            SubaddressIndexExtended {
+                index: *subaddress_index,
+                derive_type: AddressDeriveType::Carrot,
+            },
+            owned_enote.record.subaddress_index
+        );
+    }
+}
+
+#[test]
+fn batch_scan_completeness() {
+    let bob: CarrotAccount = gen_random();
+    let (batch, expected_owned) = make_batch(&bob);
+
+    let scan_result = try_scan_carrot_enotes_external_receiver_batch(
+        &batch,
+        &mut make_subaddress_table(&bob),
+        &bob,
+    )
+    .expect("try_scan_carrot_enotes_external_receiver_batch");
+
+    check_batch_scan_result(&scan_result, &expected_owned);
+}
+
+#[test]
+fn batch_scan_shared_ephemeral_pubkey() {
+    let alice: CarrotAccount = gen_random();
+    let bob: CarrotAccount = gen_random();
+
+    let tx_first_key_image: KeyImage = gen_random();
+
+    // alice pays bob and sends her change to herself in a 2-output tx sharing D_e
+    let payment_proposal = payments::CarrotPaymentProposalV1 {
+        destination: bob.main_address(),
+        amount: gen_random(),
+        randomness: gen_random(),
+    };
+    let (payment_enote_proposal, encrypted_payment_id) = payment_proposal
+        .get_normal_output_proposal(tx_first_key_image.clone())
+        .expect("get_normal_output_proposal");
+    let change_enote_proposal = payments::CarrotPaymentProposalSelfSendV1 {
+        destination_address_spend_pubkey: alice.account_spend_pubkey().clone(),
+        amount: gen_random(),
+        enote_type: CarrotEnoteType::Change,
+        enote_ephemeral_pubkey: None,
+        internal_message: None,
+    }
+    .get_special_output_proposal(
+        &alice,
+        tx_first_key_image,
+        &Some(payment_enote_proposal.enote.enote_ephemeral_pubkey.clone()),
+    )
+    .expect("get_special_output_proposal");
+
+    let batch = [
+        (
+            change_enote_proposal.enote,
+            Some(encrypted_payment_id.clone()),
+        ),
+        (payment_enote_proposal.enote, Some(encrypted_payment_id)),
+    ];
+
+    let bob_scan_result = try_scan_carrot_enotes_external_receiver_batch(
+        &batch,
+        &mut make_subaddress_table(&bob),
+        &bob,
+    )
+    .expect("try_scan_carrot_enotes_external_receiver_batch");
+    check_batch_scan_result(&bob_scan_result, &[(1, SubaddressIndex::default())]);
+    assert_eq!(
+        payment_proposal.amount,
+        bob_scan_result.owned_enotes[0].record.record.amount
+    );
+
+    let alice_scan_result = try_scan_carrot_enotes_external_receiver_batch(
+        &batch,
+        &mut make_subaddress_table(&alice),
+        &alice,
+    )
+    .expect("try_scan_carrot_enotes_external_receiver_batch");
+    check_batch_scan_result(&alice_scan_result, &[(0, SubaddressIndex::default())]);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn par_batch_scan_completeness() {
+    let bob: CarrotAccount = gen_random();
+    let (batch, expected_owned) = make_batch(&bob);
+
+    let scan_result = par_try_scan_carrot_enotes_external_receiver_batch(
+        &batch,
+        &mut make_subaddress_table(&bob),
+        &bob,
+    )
+    .expect("par_try_scan_carrot_enotes_external_receiver_batch");
+
+    check_batch_scan_result(&scan_result, &expected_owned);
+}
@@ -0,0 +1,156 @@
+#![cfg(feature = "alloc")]
+
+mod common;
+use crate::common::random::*;
+
+use carrot_crypto::commitments::*;
+use carrot_crypto::tx_builder::{CarrotTxBuilderV1, CarrotTxProposalV1};
+use carrot_crypto::*;
+
+const FEE: Amount = 30;
+
+fn make_tx_proposal(input_amount: Amount) -> CarrotTxProposalV1 {
+    let alice: CarrotAccount = gen_random();
+    let bob: CarrotAccount = gen_random();
+    let carol: CarrotAccount = gen_random();
+    CarrotTxBuilderV1::new(alice.account_spend_pubkey().clone(), input_amount)
+        .with_recipient(bob.main_address(), 1000)
+        .with_recipient(carol.main_address(), 500)
+        .with_fee(FEE)
+        .build(
+            gen_random(),
+            Some(&alice),
+            Some(&alice),
+            &mut rand_core::OsRng,
+        )
+        .expect("build")
+}
+
+#[test]
+fn commitment_balance() {
+    let input_amounts: [Amount; 3] = [700, 800, 900];
+    let tx_proposal = make_tx_proposal(input_amounts.iter().sum());
+    let output_enote_proposals = &tx_proposal.output_enote_proposals;
+
+    // pick random blinding factors for all pseudo-outputs but the last, which balances them
+    let mut pseudo_output_blinding_factors: Vec<AmountBlindingKey> =
+        (0..input_amounts.len() - 1).map(|_| gen_random()).collect();
+    pseudo_output_blinding_factors.push(make_last_pseudo_output_blinding_factor(
+        &pseudo_output_blinding_factors,
+        output_enote_proposals
+            .iter()
+            .map(|proposal| &proposal.amount_blinding_factor),
+    ));
+    assert_eq!(
+        sum_amount_blinding_factors(&pseudo_output_blinding_factors),
+        sum_amount_blinding_factors(
+            output_enote_proposals
+                .iter()
+                .map(|proposal| &proposal.amount_blinding_factor)
+        )
+    );
+
+    let pseudo_output_commitments: Vec<AmountCommitment> = input_amounts
+        .iter()
+        .zip(pseudo_output_blinding_factors.iter())
+        .map(|(amount, z)| AmountCommitment::commit(*amount, z))
+        .collect();
+    let output_commitments: Vec<&AmountCommitment> = output_enote_proposals
+        .iter()
+        .map(|proposal| &proposal.enote.amount_commitment)
+        .collect();
+
+    assert!(verify_commitment_balance(
+        &pseudo_output_commitments,
+        output_commitments.iter().copied(),
+        FEE
+    ));
+    assert!(!verify_commitment_balance(
+        &pseudo_output_commitments,
+        output_commitments.iter().copied(),
+        FEE + 1
+    ));
+    assert!(!verify_commitment_balance(
+        &pseudo_output_commitments[1..],
+        output_commitments.iter().copied(),
+        FEE
+    ));
+
+    // sum(C) of the commitments matches the commitment to the sums
+    let input_amount: Amount = input_amounts.iter().sum();
+    assert_eq!(
+        Some(AmountCommitment::commit(
+            input_amount,
+            &sum_amount_blinding_factors(&pseudo_output_blinding_factors)
+        )),
+        sum_amount_commitments(&pseudo_output_commitments)
+    );
+
+    let pseudo_output_openings: Vec<(Amount, AmountBlindingKey)> = input_amounts
+        .into_iter()
+        .zip(pseudo_output_blinding_factors)
+        .collect();
+    assert!(check_output_enote_proposals_balance(
+        &pseudo_output_openings,
+        output_enote_proposals,
+        FEE
+    ));
+    assert!(!check_output_enote_proposals_balance(
+        &pseudo_output_openings,
+        output_enote_proposals,
+        FEE - 1
+    ));
+}
+
+#[test]
+fn commitment_balance_bad_openings() {
+    let mut tx_proposal = make_tx_proposal(2000);
+    let output_enote_proposals = &mut tx_proposal.output_enote_proposals;
+    let z: AmountBlindingKey = make_last_pseudo_output_blinding_factor(
+        [],
+        output_enote_proposals
+            .iter()
+            .map(|proposal| &proposal.amount_blinding_factor),
+    );
+    let pseudo_output_openings = [(2000, z.clone())];
+    assert!(check_output_enote_proposals_balance(
+        &pseudo_output_openings,
+        output_enote_proposals,
+        FEE
+    ));
+
+    // the amounts still balance, but the commitments don't open to them anymore
+    output_enote_proposals[0].amount += 1;
+    output_enote_proposals[1].amount -= 1;
+    assert!(!check_output_enote_proposals_balance(
+        &pseudo_output_openings,
+        output_enote_proposals,
+        FEE
+    ));
+
+    // the commitments open, but the blinding factors don't balance
+    output_enote_proposals[0].amount -= 1;
+    output_enote_proposals[1].amount += 1;
+    let pseudo_output_openings = [(2000, gen_random())];
+    assert!(!check_output_enote_proposals_balance(
+        &pseudo_output_openings,
+        output_enote_proposals,
+        FEE
+    ));
+
+    // an invalid commitment point never balances, even if it would cancel out
+    let mut bad_commitment_bytes = [0; 32];
+    bad_commitment_bytes[0] = 7;
+    let bad_commitment = AmountCommitment::from_bytes(bad_commitment_bytes);
+    assert!(!verify_commitment_balance(
+        [&AmountCommitment::commit(FEE + 5, &z), &bad_commitment],
+        [&AmountCommitment::commit(5, &z), &bad_commitment],
+        FEE
+    ));
+    assert!(verify_commitment_balance(
+        [&AmountCommitment::commit(FEE + 5, &z)],
+        [&AmountCommitment::commit(5, &z)],
+        FEE
+    ));
+    assert_eq!(None, sum_amount_commitments([&bad_commitment]));
+}
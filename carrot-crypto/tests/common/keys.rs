@@ -1,36 +1,102 @@
 use carrot_crypto::{
-    opening::{OpenedPoint, OpeningScalarSecret},
+    legacy::{LegacyAccount, LegacySpendKey},
+    opening::{OpenedPoint, OpeningScalarSecret, make_carrot_address_opening},
     *,
 };
-use sha3::{Digest, Keccak256};
-use std::collections::HashMap;
+#[cfg(feature = "alloc")]
+use carrot_crypto::{
+    scan_tx::{CarrotTxScanResultV1, try_scan_carrot_tx_receiver},
+    subaddress_table::{SubaddressLookahead, SubaddressTable},
+    tx_builder::CarrotTxProposalV1,
+};
 
-#[derive(Clone, Copy)]
-pub enum AddressDeriveType {
-    Carrot,
-    Legacy,
+#[cfg(feature = "alloc")]
+pub const SUBADDRESS_LOOKAHEAD: SubaddressLookahead = SubaddressLookahead { major: 2, minor: 5 };
+
+// subaddress table of a Carrot account, for scanning enotes received to its first few subaddresses
+#[cfg(feature = "alloc")]
+pub fn make_subaddress_table(account: &CarrotAccount) -> SubaddressTable<GenerateAddressSecret> {
+    SubaddressTable::new_carrot(
+        account.account_spend_pubkey().clone(),
+        account.account_view_pubkey().clone(),
+        account.generate_address_secret().clone(),
+        SUBADDRESS_LOOKAHEAD,
+    )
+    .expect("SubaddressTable::new_carrot")
 }
 
-pub struct SubaddressIndex {
-    pub major: u32,
-    pub minor: u32,
+// scan of a tx proposal's enotes by a Carrot account
+#[cfg(feature = "alloc")]
+pub fn scan_tx_proposal(
+    account: &CarrotAccount,
+    tx_proposal: &CarrotTxProposalV1,
+) -> CarrotTxScanResultV1 {
+    let enotes: Vec<CarrotEnoteV1> = tx_proposal
+        .output_enote_proposals
+        .iter()
+        .map(|output_enote_proposal| output_enote_proposal.enote.clone())
+        .collect();
+    try_scan_carrot_tx_receiver(
+        &enotes,
+        Some(&tx_proposal.encrypted_payment_id),
+        &mut make_subaddress_table(account),
+        account,
+    )
+    .expect("try_scan_carrot_tx_receiver")
 }
 
-impl SubaddressIndex {
-    pub fn is_subaddress(&self) -> bool {
-        return self.major != 0 || self.minor != 0;
-    }
+// records of the enotes in a tx proposal which a Carrot account owns
+#[cfg(feature = "alloc")]
+pub fn receive_tx_proposal(
+    account: &CarrotAccount,
+    tx_proposal: &CarrotTxProposalV1,
+) -> Vec<CarrotOwnedEnoteRecordV1> {
+    scan_tx_proposal(account, tx_proposal)
+        .owned_enotes
+        .into_iter()
+        .map(|owned_enote| owned_enote.record)
+        .collect()
 }
 
-pub struct SubaddressIndexExtended {
-    pub index: SubaddressIndex,
-    pub derive_type: Option<AddressDeriveType>,
+// L of an enote owned by a Carrot account
+pub fn key_image(account: &CarrotAccount, record: &CarrotOwnedEnoteRecordV1) -> KeyImage {
+    account
+        .enote_record_key_image(record)
+        .expect("enote_record_key_image")
+}
+
+// coinbase enote paid to a Carrot account's main address, scanned back by that account
+#[cfg(feature = "alloc")]
+pub fn receive_coinbase(
+    account: &CarrotAccount,
+    amount: Amount,
+    block_index: BlockIndex,
+) -> CarrotOwnedEnoteRecordV1 {
+    let enote = payments::CarrotPaymentProposalV1 {
+        destination: account.main_address(),
+        amount,
+        randomness: crate::common::random::gen_random(),
+    }
+    .get_coinbase_output_proposal(block_index)
+    .expect("get_coinbase_output_proposal");
+    let s_sender_receiver_unctx = scan::make_carrot_uncontextualized_shared_key_receiver(
+        account,
+        &enote.enote_ephemeral_pubkey,
+    )
+    .expect("make_carrot_uncontextualized_shared_key_receiver");
+    CarrotOwnedEnoteRecordV1::Coinbase(
+        scan::try_scan_carrot_coinbase_enote_receiver_with_subaddress_table(
+            &enote,
+            &s_sender_receiver_unctx,
+            &mut make_subaddress_table(account),
+        )
+        .expect("try_scan_carrot_coinbase_enote_receiver_with_subaddress_table"),
+    )
 }
 
 pub struct MockKeys {
     // legacy privkeys and pubkeys
-    pub legacy_k_spend: ProveSpendKey,
-    pub legacy_account_spend_pubkey: AddressSpendPubkey,
+    pub legacy_account: LegacyAccount,
 
     // carrot secret keys (minus k_v, which is shared with legacy k_v)
     pub s_master: MasterSecret,
@@ -47,7 +113,8 @@ pub struct MockKeys {
     pub carrot_account_spend_pubkey: AddressSpendPubkey,
     pub carrot_account_view_pubkey: AddressViewPubkey,
 
-    pub subaddress_map: HashMap<AddressSpendPubkey, SubaddressIndexExtended>,
+    #[cfg(feature = "alloc")]
+    pub subaddress_table: SubaddressTable<GenerateAddressSecret>,
 
     pub default_derive_type: AddressDeriveType,
 }
@@ -56,7 +123,7 @@ impl MockKeys {
     pub fn main_address(&self, derive_type: Option<AddressDeriveType>) -> CarrotDestinationV1 {
         let account_spend_pubkey = match self.resolve_derive_type(derive_type) {
             AddressDeriveType::Carrot => &self.carrot_account_spend_pubkey,
-            AddressDeriveType::Legacy => &self.legacy_account_spend_pubkey,
+            AddressDeriveType::Legacy => self.legacy_account.account_spend_pubkey(),
         };
 
         CarrotDestinationV1::make_main_address(
@@ -72,7 +139,7 @@ impl MockKeys {
     ) -> CarrotDestinationV1 {
         let account_spend_pubkey = match self.resolve_derive_type(derive_type) {
             AddressDeriveType::Carrot => &self.carrot_account_spend_pubkey,
-            AddressDeriveType::Legacy => &self.legacy_account_spend_pubkey,
+            AddressDeriveType::Legacy => self.legacy_account.account_spend_pubkey(),
         };
 
         CarrotDestinationV1::make_integrated_address(
@@ -82,8 +149,18 @@ impl MockKeys {
         )
     }
 
-    pub fn subaddress(&self, subaddr_index: &SubaddressIndexExtended) -> CarrotDestinationV1 {
-        match self.resolve_derive_type(subaddr_index.derive_type) {
+    pub fn subaddress(&self, subaddr_index: &SubaddressIndex) -> CarrotDestinationV1 {
+        self.subaddress_with_derive_type(&SubaddressIndexExtended {
+            index: *subaddr_index,
+            derive_type: self.default_derive_type,
+        })
+    }
+
+    pub fn subaddress_with_derive_type(
+        &self,
+        subaddr_index: &SubaddressIndexExtended,
+    ) -> CarrotDestinationV1 {
+        match subaddr_index.derive_type {
             AddressDeriveType::Carrot => {
                 if subaddr_index.index.is_subaddress() {
                     CarrotDestinationV1::make_subaddress(
@@ -101,12 +178,9 @@ impl MockKeys {
                     )
                 }
             }
-            AddressDeriveType::Legacy => Self::make_legacy_subaddress(
-                &self.k_view_incoming,
-                &self.legacy_account_spend_pubkey,
-                subaddr_index.index.major,
-                subaddr_index.index.minor,
-            ),
+            AddressDeriveType::Legacy => self
+                .legacy_account
+                .subaddress(subaddr_index.index.major, subaddr_index.index.minor),
         }
     }
 
@@ -114,66 +188,37 @@ impl MockKeys {
         &self,
         subaddr_index: &SubaddressIndexExtended,
     ) -> (OpeningScalarSecret, OpeningScalarSecret, AddressSpendPubkey) {
-        let is_subaddress = subaddr_index.index.is_subaddress();
         let major_index = subaddr_index.index.major;
         let minor_index = subaddr_index.index.minor;
 
-        let (address_privkey_g, address_privkey_t) =
-            match self.resolve_derive_type(subaddr_index.derive_type) {
-                AddressDeriveType::Carrot => {
-                    // s^j_ap1 = H_32[s_ga](j_major, j_minor)
-                    let s_address_index_preimage_1 = AddressIndexPreimage1::derive(
-                        &self.s_generate_address,
-                        major_index,
-                        minor_index,
-                    );
-
-                    // s^j_ap2 = H_32[s^j_ap1](j_major, j_minor, K_s, K_v)
-                    let s_address_index_preimage_2 = AddressIndexPreimage2::derive(
-                        &s_address_index_preimage_1,
-                        major_index,
-                        minor_index,
-                        &self.carrot_account_spend_pubkey,
-                        &self.carrot_account_view_pubkey,
-                    );
-
-                    let subaddress_scalar = if is_subaddress {
-                        // k^j_subscal = H_n[s^j_gen](K_s, K_v, j_major, j_minor)
-                        SubaddressScalarSecret::derive(
-                            &s_address_index_preimage_2,
-                            &self.carrot_account_spend_pubkey,
-                        )
-                    } else {
-                        // k^j_subscal = 1
-                        SubaddressScalarSecret::from(1u64)
-                    };
-
-                    // k^g_a = k_gi * k^j_subscal
-                    let address_privkey_g = &self.k_generate_image * &subaddress_scalar;
-
-                    // k^t_a = k_ps * k^j_subscal
-                    let address_privkey_t = &self.k_prove_spend * &subaddress_scalar;
-
-                    (address_privkey_g, address_privkey_t)
-                }
-                AddressDeriveType::Legacy => {
-                    // m = Hn(k_v || j_major || j_minor) if subaddress else 0
-                    let subaddress_extension = Self::make_legacy_subaddress_extension(
-                        &self.k_view_incoming,
-                        major_index,
-                        minor_index,
-                    );
-
-                    // k^g_a = k_s + m
-                    let address_privkey_g = &self.legacy_k_spend + &subaddress_extension;
-
-                    // k^t_a = 0
-                    (address_privkey_g, OpeningScalarSecret::default())
-                }
-            };
+        let (address_privkey_g, address_privkey_t) = match subaddr_index.derive_type {
+            AddressDeriveType::Carrot => {
+                // k^j_subscal = H_n[s^j_ap2](K_s) if subaddress else 1
+                let subaddress_scalar = SubaddressScalarSecret::derive_from_index(
+                    &self.s_generate_address,
+                    &self.carrot_account_spend_pubkey,
+                    &self.carrot_account_view_pubkey,
+                    major_index,
+                    minor_index,
+                )
+                .expect("SubaddressScalarSecret::derive_from_index");
+
+                // k^g_a = k_gi * k^j_subscal, k^t_a = k_ps * k^j_subscal
+                make_carrot_address_opening(
+                    &self.k_prove_spend,
+                    &self.k_generate_image,
+                    &subaddress_scalar,
+                )
+            }
+            AddressDeriveType::Legacy => {
+                // k^g_a = k_s + m, k^t_a = 0
+                self.legacy_account
+                    .opening_for_subaddress(major_index, minor_index)
+            }
+        };
 
         // perform sanity check
-        let addr = self.subaddress(subaddr_index);
+        let addr = self.subaddress_with_derive_type(subaddr_index);
         let recomputed_address_spend_pubkey = AddressSpendPubkey::from(OpenedPoint::scalar_mul_gt(
             &address_privkey_g,
             &address_privkey_t,
@@ -192,10 +237,10 @@ impl MockKeys {
         &self,
         address_spend_pubkey: &AddressSpendPubkey,
     ) -> Option<(OpeningScalarSecret, OpeningScalarSecret)> {
-        let subaddr_index = self.subaddress_map.get(address_spend_pubkey)?;
+        let subaddr_index = self.find_subaddress_index(address_spend_pubkey)?;
 
         let (address_privkey_g, address_privkey_t, recomputed_address_spend_pubkey) =
-            self.opening_for_subaddress(subaddr_index);
+            self.opening_for_subaddress(&subaddr_index);
 
         assert_eq!(&recomputed_address_spend_pubkey, address_spend_pubkey);
 
@@ -252,94 +297,59 @@ impl MockKeys {
         &recomputed_onetime_address == onetime_address
     }
 
-    fn resolve_derive_type(&self, derive_type: Option<AddressDeriveType>) -> AddressDeriveType {
-        derive_type.unwrap_or(self.default_derive_type)
-    }
-
-    fn make_legacy_subaddress_extension(
-        k_view: &ViewIncomingKey,
-        major_index: u32,
-        minor_index: u32,
-    ) -> OpeningScalarSecret {
-        if major_index == 0 && minor_index == 0 {
-            return OpeningScalarSecret::default();
-        }
-
-        let mut data = [0u8; (8 + 32 + 4 + 4)];
-        // "Subaddr" || IntToBytes(0)
-        data[0..7].copy_from_slice("SubAddr".as_bytes());
-        data[8] = 0;
-        // ... || k_v
-        data[8..40].copy_from_slice(k_view.as_bytes());
-        // ... || IntToBytes32(j_major)
-        data[40..44].copy_from_slice(&major_index.to_be_bytes());
-        // ... || IntToBytes32(j_minor)
-        data[44..48].copy_from_slice(&minor_index.to_be_bytes());
-
-        // k^j_subext = ScalarDeriveLegacy("SubAddr" || IntToBytes8(0) || k_v || IntToBytes32(j_major) || IntToBytes32(j_minor))
-        let mut hasher = Keccak256::default();
-        hasher.update(&data);
-        let hash = hasher.finalize();
-        OpeningScalarSecret::from_bytes_mod_order(hash.into())
+    #[cfg(feature = "alloc")]
+    fn find_subaddress_index(
+        &self,
+        address_spend_pubkey: &AddressSpendPubkey,
+    ) -> Option<SubaddressIndexExtended> {
+        self.subaddress_table.get(address_spend_pubkey).copied()
     }
 
-    fn make_legacy_view_key(legacy_k_spend: &ProveSpendKey) -> ViewIncomingKey {
-        let mut hasher = Keccak256::default();
-        hasher.update(legacy_k_spend.as_bytes());
-        let hash = hasher.finalize();
-        ViewIncomingKey::from_bytes_mod_order(hash.into())
+    // without a subaddress table, search all subaddresses up to the max indices
+    #[cfg(not(feature = "alloc"))]
+    fn find_subaddress_index(
+        &self,
+        address_spend_pubkey: &AddressSpendPubkey,
+    ) -> Option<SubaddressIndexExtended> {
+        let derive_types = [AddressDeriveType::Carrot, AddressDeriveType::Legacy];
+        (0..=crate::common::MAX_SUBADDRESS_MAJOR_INDEX)
+            .flat_map(|major| {
+                (0..=crate::common::MAX_SUBADDRESS_MINOR_INDEX).map(move |minor| (major, minor))
+            })
+            .flat_map(|(major, minor)| {
+                derive_types.map(|derive_type| SubaddressIndexExtended {
+                    index: SubaddressIndex { major, minor },
+                    derive_type,
+                })
+            })
+            .find(|subaddr_index| {
+                &self
+                    .subaddress_with_derive_type(subaddr_index)
+                    .address_spend_pubkey
+                    == address_spend_pubkey
+            })
     }
 
-    fn make_legacy_subaddress(
-        k_view: &ViewIncomingKey,
-        account_spend_pubkey: &AddressSpendPubkey,
-        major_index: u32,
-        minor_index: u32,
-    ) -> CarrotDestinationV1 {
-        let subaddress_extension_scalar =
-            Self::make_legacy_subaddress_extension(&k_view, major_index, minor_index);
-        let subaddress_extension = OpenedPoint::scalar_mul_gt(
-            &subaddress_extension_scalar,
-            &OpeningScalarSecret::default(),
-        );
-        let address_spend_pubkey =
-            AddressSpendPubkey::from(account_spend_pubkey + &subaddress_extension);
-        let address_view_pubkey =
-            AddressViewPubkey::derive_carrot_account_view_pubkey(k_view, &address_spend_pubkey)
-                .expect("derive_carrot_account_view_pubkey (legacy)");
-        let is_subaddress = major_index != 0 || minor_index != 0;
-        CarrotDestinationV1 {
-            address_spend_pubkey: address_spend_pubkey,
-            address_view_pubkey: address_view_pubkey,
-            is_subaddress: is_subaddress,
-            payment_id: PaymentId::default(),
-        }
+    fn resolve_derive_type(&self, derive_type: Option<AddressDeriveType>) -> AddressDeriveType {
+        derive_type.unwrap_or(self.default_derive_type)
     }
 
     fn from_master_keys(
         s_master: MasterSecret,
-        legacy_k_spend: ProveSpendKey,
+        legacy_k_spend: LegacySpendKey,
         default_derive_type: AddressDeriveType,
     ) -> Self {
-        // derive legacy pubkeys
-        let legacy_account_spend_pubkey = AddressSpendPubkey::derive_carrot_account_spend_pubkey(
-            &GenerateImageKey::from_bytes_mod_order(legacy_k_spend.as_bytes().clone()),
-            &ProveSpendKey::default(),
-        );
-
         // derive carrot privkeys
-        let k_prove_spend = ProveSpendKey::derive(&s_master);
-        let partial_account_spend_pubkey = PartialAccountSpendPubkey::derive(&k_prove_spend);
-        let s_view_balance = ViewBalanceSecret::derive(&s_master);
-        let s_generate_image_preimage = GenerateImagePreimage::derive(&s_view_balance);
-        let k_generate_image =
-            GenerateImageKey::derive(&s_generate_image_preimage, &partial_account_spend_pubkey);
-        let s_generate_address = GenerateAddressSecret::derive(&s_view_balance);
+        let carrot_account = CarrotAccount::from_master_secret(s_master.clone());
+        let k_prove_spend = carrot_account.prove_spend_key().clone();
+        let s_view_balance = carrot_account.view_balance_secret().clone();
+        let k_generate_image = carrot_account.generate_image_key().clone();
+        let s_generate_address = carrot_account.generate_address_secret().clone();
 
         // derive view-incoming {pub/priv}key, dependent on address derive type
         let k_view_incoming = match default_derive_type {
-            AddressDeriveType::Carrot => ViewIncomingKey::derive(&s_view_balance),
-            AddressDeriveType::Legacy => Self::make_legacy_view_key(&legacy_k_spend),
+            AddressDeriveType::Carrot => carrot_account.view_incoming_key().clone(),
+            AddressDeriveType::Legacy => ViewIncomingKey::derive_legacy(&legacy_k_spend),
         };
         let primary_address_view_pubkey =
             AddressViewPubkey::derive_primary_address_view_pubkey(&k_view_incoming);
@@ -355,55 +365,29 @@ impl MockKeys {
         )
         .unwrap();
 
-        // derive subaddress map, Carrot and Legacy
-        let mut subaddress_map = HashMap::new();
-        let derive_types = [AddressDeriveType::Carrot, AddressDeriveType::Legacy];
-        for major_index in 0..=crate::common::MAX_SUBADDRESS_MAJOR_INDEX {
-            for minor_index in 0..=crate::common::MAX_SUBADDRESS_MINOR_INDEX {
-                for derive_type in derive_types.iter() {
-                    let address_spend_pubkey = match derive_type {
-                        AddressDeriveType::Carrot => {
-                            if major_index != 0 || minor_index != 0 {
-                                CarrotDestinationV1::make_subaddress(
-                                    &carrot_account_spend_pubkey,
-                                    &carrot_account_view_pubkey,
-                                    &s_generate_address,
-                                    major_index,
-                                    minor_index,
-                                )
-                                .unwrap()
-                                .address_spend_pubkey
-                            } else {
-                                carrot_account_spend_pubkey.clone()
-                            }
-                        }
-                        AddressDeriveType::Legacy => {
-                            Self::make_legacy_subaddress(
-                                &k_view_incoming,
-                                &legacy_account_spend_pubkey,
-                                major_index,
-                                minor_index,
-                            )
-                            .address_spend_pubkey
-                        }
-                    };
-                    subaddress_map.insert(
-                        address_spend_pubkey,
-                        SubaddressIndexExtended {
-                            index: SubaddressIndex {
-                                major: major_index,
-                                minor: minor_index,
-                            },
-                            derive_type: Some(derive_type.clone()),
-                        },
-                    );
-                }
-            }
-        }
+        // legacy account shares the view-incoming key, even when deriving Carrot addresses
+        let legacy_account = LegacyAccount::new(legacy_k_spend, k_view_incoming.clone());
+
+        // derive subaddress table, Carrot and Legacy
+        #[cfg(feature = "alloc")]
+        let subaddress_table = SubaddressTable::new_carrot(
+            carrot_account_spend_pubkey.clone(),
+            carrot_account_view_pubkey.clone(),
+            s_generate_address.clone(),
+            SubaddressLookahead {
+                major: crate::common::MAX_SUBADDRESS_MAJOR_INDEX + 1,
+                minor: crate::common::MAX_SUBADDRESS_MINOR_INDEX + 1,
+            },
+        )
+        .unwrap()
+        .with_legacy(
+            k_view_incoming.clone(),
+            legacy_account.account_spend_pubkey().clone(),
+        )
+        .unwrap();
 
         Self {
-            legacy_k_spend: legacy_k_spend,
-            legacy_account_spend_pubkey: legacy_account_spend_pubkey,
+            legacy_account: legacy_account,
             s_master: s_master,
             k_prove_spend: k_prove_spend,
             s_view_balance: s_view_balance,
@@ -413,7 +397,8 @@ impl MockKeys {
             primary_address_view_pubkey: primary_address_view_pubkey,
             carrot_account_spend_pubkey: carrot_account_spend_pubkey,
             carrot_account_view_pubkey: carrot_account_view_pubkey,
-            subaddress_map: subaddress_map,
+            #[cfg(feature = "alloc")]
+            subaddress_table: subaddress_table,
             default_derive_type: default_derive_type,
         }
     }
@@ -427,7 +412,7 @@ impl random::Random for MockKeys {
     ) -> Self {
         Self::from_master_keys(
             MasterSecret::new_random_with_params(rng, ()),
-            ProveSpendKey::new_random_with_params(rng, ()),
+            LegacySpendKey::new_random_with_params(rng, ()),
             p,
         )
     }
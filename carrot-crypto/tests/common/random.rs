@@ -1,7 +1,6 @@
 use carrot_crypto::random::{Random, new_random};
 use carrot_crypto::*;
 
-use crate::common::keys::{SubaddressIndex, SubaddressIndexExtended};
 use crate::common::{MAX_SUBADDRESS_MAJOR_INDEX, MAX_SUBADDRESS_MINOR_INDEX};
 
 pub fn gen_random<R>() -> R
@@ -26,13 +25,10 @@ pub fn gen_subaddress_index_minor() -> u32 {
     1 + gen_random::<u32>() % MAX_SUBADDRESS_MINOR_INDEX
 }
 
-pub fn gen_subaddress_index() -> SubaddressIndexExtended {
-    SubaddressIndexExtended {
-        index: SubaddressIndex {
-            major: gen_subaddress_index_major(),
-            minor: gen_subaddress_index_minor(),
-        },
-        derive_type: None,
+pub fn gen_subaddress_index() -> SubaddressIndex {
+    SubaddressIndex {
+        major: gen_subaddress_index_major(),
+        minor: gen_subaddress_index_minor(),
     }
 }
 
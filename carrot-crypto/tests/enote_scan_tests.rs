@@ -2,6 +2,8 @@ mod common;
 use crate::common::keys::*;
 use crate::common::random::*;
 
+#[cfg(feature = "alloc")]
+use carrot_crypto::subaddress_table::{SubaddressLookahead, SubaddressTable};
 use carrot_crypto::*;
 
 use std::sync::LazyLock;
@@ -42,15 +44,7 @@ fn main_address_normal_scan_completeness() {
         &enote_proposal.enote.enote_ephemeral_pubkey,
     );
 
-    let (
-        recovered_sender_extension_g,
-        recovered_sender_extension_t,
-        recovered_address_spend_pubkey,
-        recovered_amount,
-        recovered_amount_blinding_factor,
-        recovered_payment_id,
-        recovered_enote_type,
-    ) = scan::try_scan_carrot_enote_external_receiver(
+    let enote_record = scan::try_scan_carrot_enote_external_receiver(
         &enote_proposal.enote,
         Some(&encrypted_payment_id),
         &s_sender_receiver_unctx,
@@ -62,21 +56,21 @@ fn main_address_normal_scan_completeness() {
     // check recovered data
     assert_eq!(
         proposal.destination.address_spend_pubkey,
-        recovered_address_spend_pubkey
+        enote_record.address_spend_pubkey
     );
-    assert_eq!(proposal.amount, recovered_amount);
+    assert_eq!(proposal.amount, enote_record.amount);
     assert_eq!(
         enote_proposal.amount_blinding_factor,
-        recovered_amount_blinding_factor
+        enote_record.amount_blinding_factor
     );
-    assert_eq!(PaymentId::default(), recovered_payment_id);
-    assert_eq!(CarrotEnoteType::Payment, recovered_enote_type);
+    assert_eq!(PaymentId::default(), enote_record.payment_id);
+    assert_eq!(CarrotEnoteType::Payment, enote_record.enote_type);
 
     // check spendability
     assert!(keys.can_open_fcmp_onetime_address(
-        &recovered_address_spend_pubkey,
-        &recovered_sender_extension_g,
-        &recovered_sender_extension_t,
+        &enote_record.address_spend_pubkey,
+        &enote_record.sender_extension_g,
+        &enote_record.sender_extension_t,
         &enote_proposal.enote.onetime_address
     ));
 }
@@ -117,15 +111,7 @@ fn subaddress_normal_scan_completeness() {
     )
     .expect("make_carrot_uncontextualized_shared_key_receiver");
 
-    let (
-        recovered_sender_extension_g,
-        recovered_sender_extension_t,
-        recovered_address_spend_pubkey,
-        recovered_amount,
-        recovered_amount_blinding_factor,
-        recovered_payment_id,
-        recovered_enote_type,
-    ) = scan::try_scan_carrot_enote_external_receiver(
+    let enote_record = scan::try_scan_carrot_enote_external_receiver(
         &enote_proposal.enote,
         Some(&encrypted_payment_id),
         &s_sender_receiver_unctx,
@@ -137,21 +123,21 @@ fn subaddress_normal_scan_completeness() {
     // check recovered data
     assert_eq!(
         proposal.destination.address_spend_pubkey,
-        recovered_address_spend_pubkey
+        enote_record.address_spend_pubkey
     );
-    assert_eq!(proposal.amount, recovered_amount);
+    assert_eq!(proposal.amount, enote_record.amount);
     assert_eq!(
         enote_proposal.amount_blinding_factor,
-        recovered_amount_blinding_factor
+        enote_record.amount_blinding_factor
     );
-    assert_eq!(PaymentId::default(), recovered_payment_id);
-    assert_eq!(CarrotEnoteType::Payment, recovered_enote_type);
+    assert_eq!(PaymentId::default(), enote_record.payment_id);
+    assert_eq!(CarrotEnoteType::Payment, enote_record.enote_type);
 
     // check spendability
     assert!(keys.can_open_fcmp_onetime_address(
-        &recovered_address_spend_pubkey,
-        &recovered_sender_extension_g,
-        &recovered_sender_extension_t,
+        &enote_record.address_spend_pubkey,
+        &enote_record.sender_extension_g,
+        &enote_record.sender_extension_t,
         &enote_proposal.enote.onetime_address
     ));
 }
@@ -191,15 +177,7 @@ fn integrated_address_normal_scan_completeness() {
     )
     .expect("make_carrot_uncontextualized_shared_key_receiver");
 
-    let (
-        recovered_sender_extension_g,
-        recovered_sender_extension_t,
-        recovered_address_spend_pubkey,
-        recovered_amount,
-        recovered_amount_blinding_factor,
-        recovered_payment_id,
-        recovered_enote_type,
-    ) = scan::try_scan_carrot_enote_external_receiver(
+    let enote_record = scan::try_scan_carrot_enote_external_receiver(
         &enote_proposal.enote,
         Some(&encrypted_payment_id),
         &s_sender_receiver_unctx,
@@ -211,21 +189,21 @@ fn integrated_address_normal_scan_completeness() {
     // check recovered data
     assert_eq!(
         proposal.destination.address_spend_pubkey,
-        recovered_address_spend_pubkey
+        enote_record.address_spend_pubkey
     );
-    assert_eq!(proposal.amount, recovered_amount);
+    assert_eq!(proposal.amount, enote_record.amount);
     assert_eq!(
         enote_proposal.amount_blinding_factor,
-        recovered_amount_blinding_factor
+        enote_record.amount_blinding_factor
     );
-    assert_eq!(integrated_address.payment_id, recovered_payment_id);
-    assert_eq!(CarrotEnoteType::Payment, recovered_enote_type);
+    assert_eq!(integrated_address.payment_id, enote_record.payment_id);
+    assert_eq!(CarrotEnoteType::Payment, enote_record.enote_type);
 
     // check spendability
     assert!(keys.can_open_fcmp_onetime_address(
-        &recovered_address_spend_pubkey,
-        &recovered_sender_extension_g,
-        &recovered_sender_extension_t,
+        &enote_record.address_spend_pubkey,
+        &enote_record.sender_extension_g,
+        &enote_record.sender_extension_t,
         &enote_proposal.enote.onetime_address
     ));
 }
@@ -266,15 +244,7 @@ fn main_address_special_scan_completeness() {
         )
         .expect("make_carrot_uncontextualized_shared_key_receiver");
 
-        let (
-            recovered_sender_extension_g,
-            recovered_sender_extension_t,
-            recovered_address_spend_pubkey,
-            recovered_amount,
-            recovered_amount_blinding_factor,
-            recovered_payment_id,
-            recovered_enote_type,
-        ) = scan::try_scan_carrot_enote_external_receiver(
+        let enote_record = scan::try_scan_carrot_enote_external_receiver(
             &enote_proposal.enote,
             None,
             &s_sender_receiver_unctx,
@@ -286,21 +256,21 @@ fn main_address_special_scan_completeness() {
         // check recovered data
         assert_eq!(
             proposal.destination_address_spend_pubkey,
-            recovered_address_spend_pubkey
+            enote_record.address_spend_pubkey
         );
-        assert_eq!(proposal.amount, recovered_amount);
+        assert_eq!(proposal.amount, enote_record.amount);
         assert_eq!(
             enote_proposal.amount_blinding_factor,
-            recovered_amount_blinding_factor
+            enote_record.amount_blinding_factor
         );
-        assert_eq!(PaymentId::default(), recovered_payment_id);
-        assert_eq!(enote_type, recovered_enote_type);
+        assert_eq!(PaymentId::default(), enote_record.payment_id);
+        assert_eq!(enote_type, enote_record.enote_type);
 
         // check spendability
         assert!(keys.can_open_fcmp_onetime_address(
-            &recovered_address_spend_pubkey,
-            &recovered_sender_extension_g,
-            &recovered_sender_extension_t,
+            &enote_record.address_spend_pubkey,
+            &enote_record.sender_extension_g,
+            &enote_record.sender_extension_t,
             &enote_proposal.enote.onetime_address
         ));
     }
@@ -346,15 +316,7 @@ fn subaddress_special_scan_completeness() {
         )
         .expect("make_carrot_uncontextualized_shared_key_receiver");
 
-        let (
-            recovered_sender_extension_g,
-            recovered_sender_extension_t,
-            recovered_address_spend_pubkey,
-            recovered_amount,
-            recovered_amount_blinding_factor,
-            recovered_payment_id,
-            recovered_enote_type,
-        ) = scan::try_scan_carrot_enote_external_receiver(
+        let enote_record = scan::try_scan_carrot_enote_external_receiver(
             &enote_proposal.enote,
             None,
             &s_sender_receiver_unctx,
@@ -366,21 +328,21 @@ fn subaddress_special_scan_completeness() {
         // check recovered data
         assert_eq!(
             proposal.destination_address_spend_pubkey,
-            recovered_address_spend_pubkey
+            enote_record.address_spend_pubkey
         );
-        assert_eq!(proposal.amount, recovered_amount);
+        assert_eq!(proposal.amount, enote_record.amount);
         assert_eq!(
             enote_proposal.amount_blinding_factor,
-            recovered_amount_blinding_factor
+            enote_record.amount_blinding_factor
         );
-        assert_eq!(PaymentId::default(), recovered_payment_id);
-        assert_eq!(enote_type, recovered_enote_type);
+        assert_eq!(PaymentId::default(), enote_record.payment_id);
+        assert_eq!(enote_type, enote_record.enote_type);
 
         // check spendability
         assert!(keys.can_open_fcmp_onetime_address(
-            &recovered_address_spend_pubkey,
-            &recovered_sender_extension_g,
-            &recovered_sender_extension_t,
+            &enote_record.address_spend_pubkey,
+            &enote_record.sender_extension_g,
+            &enote_record.sender_extension_t,
             &enote_proposal.enote.onetime_address
         ));
     }
@@ -418,15 +380,7 @@ fn main_address_internal_scan_completeness() {
             recomputed_amount_commitment
         );
 
-        let (
-            recovered_sender_extension_g,
-            recovered_sender_extension_t,
-            recovered_address_spend_pubkey,
-            recovered_amount,
-            recovered_amount_blinding_factor,
-            recovered_enote_type,
-            recovered_internal_message,
-        ) = scan::try_scan_carrot_enote_internal_receiver(
+        let enote_record = scan::try_scan_carrot_enote_internal_receiver(
             &enote_proposal.enote,
             &keys.s_view_balance,
         )
@@ -435,21 +389,24 @@ fn main_address_internal_scan_completeness() {
         // check recovered data
         assert_eq!(
             proposal.destination_address_spend_pubkey,
-            recovered_address_spend_pubkey
+            enote_record.address_spend_pubkey
         );
-        assert_eq!(proposal.amount, recovered_amount);
+        assert_eq!(proposal.amount, enote_record.amount);
         assert_eq!(
             enote_proposal.amount_blinding_factor,
-            recovered_amount_blinding_factor
+            enote_record.amount_blinding_factor
+        );
+        assert_eq!(enote_type, enote_record.enote_type);
+        assert_eq!(
+            proposal.internal_message,
+            Some(enote_record.internal_message)
         );
-        assert_eq!(enote_type, recovered_enote_type);
-        assert_eq!(proposal.internal_message, Some(recovered_internal_message));
 
         // check spendability
         assert!(keys.can_open_fcmp_onetime_address(
-            &recovered_address_spend_pubkey,
-            &recovered_sender_extension_g,
-            &recovered_sender_extension_t,
+            &enote_record.address_spend_pubkey,
+            &enote_record.sender_extension_g,
+            &enote_record.sender_extension_t,
             &enote_proposal.enote.onetime_address
         ));
     }
@@ -489,15 +446,7 @@ fn subaddress_internal_scan_completeness() {
             recomputed_amount_commitment
         );
 
-        let (
-            recovered_sender_extension_g,
-            recovered_sender_extension_t,
-            recovered_address_spend_pubkey,
-            recovered_amount,
-            recovered_amount_blinding_factor,
-            recovered_enote_type,
-            recovered_internal_message,
-        ) = scan::try_scan_carrot_enote_internal_receiver(
+        let enote_record = scan::try_scan_carrot_enote_internal_receiver(
             &enote_proposal.enote,
             &keys.s_view_balance,
         )
@@ -506,21 +455,24 @@ fn subaddress_internal_scan_completeness() {
         // check recovered data
         assert_eq!(
             proposal.destination_address_spend_pubkey,
-            recovered_address_spend_pubkey
+            enote_record.address_spend_pubkey
         );
-        assert_eq!(proposal.amount, recovered_amount);
+        assert_eq!(proposal.amount, enote_record.amount);
         assert_eq!(
             enote_proposal.amount_blinding_factor,
-            recovered_amount_blinding_factor
+            enote_record.amount_blinding_factor
+        );
+        assert_eq!(enote_type, enote_record.enote_type);
+        assert_eq!(
+            proposal.internal_message,
+            Some(enote_record.internal_message)
         );
-        assert_eq!(enote_type, recovered_enote_type);
-        assert_eq!(proposal.internal_message, Some(recovered_internal_message));
 
         // check spendability
         assert!(keys.can_open_fcmp_onetime_address(
-            &recovered_address_spend_pubkey,
-            &recovered_sender_extension_g,
-            &recovered_sender_extension_t,
+            &enote_record.address_spend_pubkey,
+            &enote_record.sender_extension_g,
+            &enote_record.sender_extension_t,
             &enote_proposal.enote.onetime_address
         ));
     }
@@ -552,11 +504,7 @@ fn main_address_coinbase_scan_completeness() {
     )
     .expect("make_carrot_uncontextualized_shared_key_receiver");
 
-    let (
-        recovered_sender_extension_g,
-        recovered_sender_extension_t,
-        recovered_address_spend_pubkey,
-    ) = scan::try_scan_carrot_coinbase_enote_receiver(
+    let enote_record = scan::try_scan_carrot_coinbase_enote_receiver(
         &enote,
         &s_sender_receiver_unctx,
         core::slice::from_ref(&keys.carrot_account_spend_pubkey),
@@ -566,14 +514,172 @@ fn main_address_coinbase_scan_completeness() {
     // check recovered data
     assert_eq!(
         proposal.destination.address_spend_pubkey,
-        recovered_address_spend_pubkey
+        enote_record.address_spend_pubkey
     );
 
     // check spendability
     assert!(keys.can_open_fcmp_onetime_address(
         &keys.carrot_account_spend_pubkey,
-        &recovered_sender_extension_g,
-        &recovered_sender_extension_t,
+        &enote_record.sender_extension_g,
+        &enote_record.sender_extension_t,
         &enote.onetime_address
     ));
 }
+
+#[test]
+fn main_address_normal_scan_wrong_receiver() {
+    let keys = &*BOB_CARROT_KEYS;
+    let other_keys: MockKeys = gen_random_with_params(AddressDeriveType::Carrot);
+
+    let proposal = payments::CarrotPaymentProposalV1 {
+        destination: other_keys.main_address(None),
+        amount: gen_random(),
+        randomness: gen_random(),
+    };
+
+    let (enote_proposal, encrypted_payment_id) = proposal
+        .get_normal_output_proposal(gen_random())
+        .expect("get_normal_output_proposal");
+
+    let s_sender_receiver_unctx = scan::make_carrot_uncontextualized_shared_key_receiver(
+        &keys.k_view_incoming,
+        &enote_proposal.enote.enote_ephemeral_pubkey,
+    )
+    .expect("make_carrot_uncontextualized_shared_key_receiver");
+
+    // enote isn't addressed to us, which is almost always caught by the view tag
+    let scan_error = scan::try_scan_carrot_enote_external_receiver(
+        &enote_proposal.enote,
+        Some(&encrypted_payment_id),
+        &s_sender_receiver_unctx,
+        core::slice::from_ref(&keys.carrot_account_spend_pubkey),
+        &keys.k_view_incoming,
+    )
+    .expect_err("try_scan_carrot_enote_external_receiver");
+    assert_eq!(scan::ErrorKind::ViewTagMismatch, scan_error.kind());
+}
+
+#[test]
+fn subaddress_normal_scan_janus_failure() {
+    let keys = &*BOB_CARROT_KEYS;
+
+    let subaddress = keys.subaddress(&gen_subaddress_index());
+
+    let proposal = payments::CarrotPaymentProposalV1 {
+        destination: subaddress.clone(),
+        amount: gen_random(),
+        randomness: gen_random(),
+    };
+
+    let (enote_proposal, encrypted_payment_id) = proposal
+        .get_normal_output_proposal(gen_random())
+        .expect("get_normal_output_proposal");
+
+    let s_sender_receiver_unctx = scan::make_carrot_uncontextualized_shared_key_receiver(
+        &keys.k_view_incoming,
+        &enote_proposal.enote.enote_ephemeral_pubkey,
+    )
+    .expect("make_carrot_uncontextualized_shared_key_receiver");
+
+    // treating the subaddress as a main address makes D_e inconsistent with the address type,
+    // which is exactly what the receiver sees when a sender attempts a Janus attack
+    let scan_error = scan::try_scan_carrot_enote_external_receiver(
+        &enote_proposal.enote,
+        Some(&encrypted_payment_id),
+        &s_sender_receiver_unctx,
+        core::slice::from_ref(&subaddress.address_spend_pubkey),
+        &keys.k_view_incoming,
+    )
+    .expect_err("try_scan_carrot_enote_external_receiver");
+    assert_eq!(scan::ErrorKind::JanusProtectionFailed, scan_error.kind());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn subaddress_normal_scan_subaddress_table() {
+    let keys = &*BOB_CARROT_KEYS;
+
+    for derive_type in [AddressDeriveType::Carrot, AddressDeriveType::Legacy] {
+        let subaddr_index = SubaddressIndexExtended {
+            index: gen_subaddress_index(),
+            derive_type,
+        };
+
+        let proposal = payments::CarrotPaymentProposalV1 {
+            destination: keys.subaddress_with_derive_type(&subaddr_index),
+            amount: gen_random(),
+            randomness: gen_random(),
+        };
+
+        let (enote_proposal, encrypted_payment_id) = proposal
+            .get_normal_output_proposal(gen_random())
+            .expect("get_normal_output_proposal");
+
+        let s_sender_receiver_unctx = scan::make_carrot_uncontextualized_shared_key_receiver(
+            &keys.k_view_incoming,
+            &enote_proposal.enote.enote_ephemeral_pubkey,
+        )
+        .expect("make_carrot_uncontextualized_shared_key_receiver");
+
+        // start with a window which only covers major index 0
+        let mut subaddress_table = SubaddressTable::new_carrot(
+            keys.carrot_account_spend_pubkey.clone(),
+            keys.carrot_account_view_pubkey.clone(),
+            keys.s_generate_address.clone(),
+            SubaddressLookahead { major: 1, minor: 2 },
+        )
+        .expect("SubaddressTable::new_carrot")
+        .with_legacy(
+            keys.k_view_incoming.clone(),
+            keys.legacy_account.account_spend_pubkey().clone(),
+        )
+        .expect("SubaddressTable::with_legacy");
+
+        let scan_error = scan::try_scan_carrot_enote_external_receiver_with_subaddress_table(
+            &enote_proposal.enote,
+            Some(&encrypted_payment_id),
+            &s_sender_receiver_unctx,
+            &mut subaddress_table,
+            &keys.k_view_incoming,
+        )
+        .expect_err("try_scan_carrot_enote_external_receiver_with_subaddress_table");
+        assert_eq!(
+            scan::ErrorKind::AddressSpendPubkeyMismatch,
+            scan_error.kind()
+        );
+
+        // once the window covers the subaddress, the enote is found and tagged with its index
+        subaddress_table
+            .expand(&subaddr_index.index)
+            .expect("SubaddressTable::expand");
+        let indexed_record = scan::try_scan_carrot_enote_external_receiver_with_subaddress_table(
+            &enote_proposal.enote,
+            Some(&encrypted_payment_id),
+            &s_sender_receiver_unctx,
+            &mut subaddress_table,
+            &keys.k_view_incoming,
+        )
+        .expect("try_scan_carrot_enote_external_receiver_with_subaddress_table");
+
+        assert_eq!(subaddr_index, indexed_record.subaddress_index);
+        assert_eq!(
+            proposal.destination.address_spend_pubkey,
+            indexed_record.record.address_spend_pubkey
+        );
+        assert_eq!(proposal.amount, indexed_record.record.amount);
+
+        // receiving to the subaddress slides the window past it
+        let next_subaddr_index = SubaddressIndexExtended {
+            index: SubaddressIndex {
+                major: subaddr_index.index.major,
+                minor: subaddr_index.index.minor + 1,
+            },
+            derive_type,
+        };
+        let next_subaddress = keys.subaddress_with_derive_type(&next_subaddr_index);
+        assert_eq!(
+            Some(&next_subaddr_index),
+            subaddress_table.get(&next_subaddress.address_spend_pubkey)
+        );
+    }
+}
@@ -0,0 +1,322 @@
+#![cfg(feature = "alloc")]
+
+mod common;
+use crate::common::keys::{key_image, make_subaddress_table, receive_coinbase};
+use crate::common::random::*;
+
+use carrot_crypto::enote_store::*;
+use carrot_crypto::scan_tx::*;
+use carrot_crypto::subaddress_table::SubaddressTable;
+use carrot_crypto::*;
+
+fn receive_payment(
+    account: &CarrotAccount,
+    subaddress_table: &mut SubaddressTable<GenerateAddressSecret>,
+    destination: CarrotDestinationV1,
+    amount: Amount,
+) -> CarrotOwnedEnoteRecordV1 {
+    let proposal = payments::CarrotPaymentProposalV1 {
+        destination,
+        amount,
+        randomness: gen_random(),
+    };
+    let (enote_proposal, encrypted_payment_id) = proposal
+        .get_normal_output_proposal(gen_random())
+        .expect("get_normal_output_proposal");
+
+    let mut scan_result = try_scan_carrot_tx_receiver(
+        &[enote_proposal.enote],
+        Some(&encrypted_payment_id),
+        subaddress_table,
+        account,
+    )
+    .expect("try_scan_carrot_tx_receiver");
+    assert_eq!(1, scan_result.owned_enotes.len());
+    scan_result.owned_enotes.remove(0).record
+}
+
+#[test]
+fn enote_store_balances() {
+    let bob: CarrotAccount = gen_random();
+    let mut subaddress_table = make_subaddress_table(&bob);
+    let mut enote_store = CarrotEnoteStore::new();
+
+    // main address payment in block 100, subaddress payment in block 105, coinbase in block 100
+    let main_record = receive_payment(&bob, &mut subaddress_table, bob.main_address(), 1000);
+    let subaddress = bob.subaddress(1, 2).expect("subaddress");
+    let subaddress_record = receive_payment(&bob, &mut subaddress_table, subaddress, 200);
+    let coinbase_record = receive_coinbase(&bob, 30, 100);
+
+    let main_key_image = key_image(&bob, &main_record);
+    let subaddress_key_image = key_image(&bob, &subaddress_record);
+    let coinbase_key_image = key_image(&bob, &coinbase_record);
+
+    assert!(enote_store.add_enote(main_record.clone(), 100, Some(main_key_image.clone())));
+    assert!(enote_store.add_enote(subaddress_record, 105, Some(subaddress_key_image)));
+    assert!(enote_store.add_enote(coinbase_record, 100, Some(coinbase_key_image)));
+
+    // the same enote can't be added twice
+    assert!(!enote_store.add_enote(main_record, 100, None));
+    assert_eq!(3, enote_store.enotes().len());
+
+    // nothing is unlocked right away
+    let balance = enote_store.balance(105).expect("balance");
+    assert_eq!(0, balance.unlocked);
+    assert_eq!(1230, balance.locked);
+
+    // the payment to the main address unlocks after 10 blocks
+    let balance = enote_store.balance(110).expect("balance");
+    assert_eq!(1000, balance.unlocked);
+    assert_eq!(230, balance.locked);
+    assert_eq!(1, enote_store.spendable_enotes(110).count());
+
+    // the coinbase enote unlocks after 60 blocks
+    let balance = enote_store.balance(159).expect("balance");
+    assert_eq!(1230, balance.unlocked);
+    assert_eq!(0, balance.locked);
+
+    // balances per account and per subaddress
+    let account_0_balance = enote_store
+        .account_balance(0, AddressDeriveType::Carrot, 110)
+        .expect("account_balance");
+    assert_eq!(1000, account_0_balance.unlocked);
+    assert_eq!(30, account_0_balance.locked);
+    let account_1_balance = enote_store
+        .account_balance(1, AddressDeriveType::Carrot, 110)
+        .expect("account_balance");
+    assert_eq!(0, account_1_balance.unlocked);
+    assert_eq!(200, account_1_balance.locked);
+    let subaddress_index = |major, minor| SubaddressIndexExtended {
+        index: SubaddressIndex { major, minor },
+        derive_type: AddressDeriveType::Carrot,
+    };
+    assert_eq!(
+        200,
+        enote_store
+            .subaddress_balance(&subaddress_index(1, 2), 200)
+            .expect("subaddress_balance")
+            .unlocked
+    );
+    assert_eq!(
+        Some(CarrotBalanceV1::default()),
+        enote_store.subaddress_balance(&subaddress_index(1, 1), 200)
+    );
+
+    // spending the main address enote removes it from the balance
+    assert_eq!(
+        1,
+        enote_store.process_spent_key_images(&[gen_random(), main_key_image.clone()], 120)
+    );
+    assert_eq!(
+        0,
+        enote_store.process_spent_key_images(&[main_key_image], 121)
+    );
+    let balance = enote_store.balance(200).expect("balance");
+    assert_eq!(230, balance.unlocked);
+    assert_eq!(0, balance.locked);
+    assert_eq!(Some(120), enote_store.enotes()[0].spent_block_index);
+}
+
+#[test]
+fn enote_store_balances_by_derive_type() {
+    let bob: CarrotAccount = gen_random();
+    let mut subaddress_table = make_subaddress_table(&bob);
+    let mut enote_store = CarrotEnoteStore::new();
+
+    // an enote received to the Carrot subaddress (1, 2), and one tagged as received to the legacy
+    // subaddress at the same index
+    let subaddress = bob.subaddress(1, 2).expect("subaddress");
+    let carrot_record = receive_payment(&bob, &mut subaddress_table, subaddress.clone(), 200);
+    let mut legacy_record = receive_payment(&bob, &mut subaddress_table, subaddress, 30);
+    let CarrotOwnedEnoteRecordV1::External(indexed_record) = &mut legacy_record else {
+        panic!("expected external enote record");
+    };
+    indexed_record.subaddress_index.derive_type = AddressDeriveType::Legacy;
+    assert!(enote_store.add_enote(carrot_record, 100, None));
+    assert!(enote_store.add_enote(legacy_record, 100, None));
+
+    for (derive_type, amount) in [
+        (AddressDeriveType::Carrot, 200),
+        (AddressDeriveType::Legacy, 30),
+    ] {
+        let subaddress_index = SubaddressIndexExtended {
+            index: SubaddressIndex { major: 1, minor: 2 },
+            derive_type,
+        };
+        assert_eq!(
+            amount,
+            enote_store
+                .subaddress_balance(&subaddress_index, 200)
+                .expect("subaddress_balance")
+                .unlocked
+        );
+        assert_eq!(
+            amount,
+            enote_store
+                .account_balance(1, derive_type, 200)
+                .expect("account_balance")
+                .unlocked
+        );
+    }
+    assert_eq!(230, enote_store.balance(200).expect("balance").unlocked);
+}
+
+#[test]
+fn enote_store_balance_overflow() {
+    let bob: CarrotAccount = gen_random();
+    let mut enote_store = CarrotEnoteStore::new();
+
+    let coinbase_record = receive_coinbase(&bob, Amount::MAX, 100);
+    assert!(enote_store.add_enote(coinbase_record, 100, None));
+    let balance = enote_store.balance(200).expect("balance");
+    assert_eq!(Amount::MAX, balance.unlocked);
+    assert_eq!(Some(Amount::MAX), balance.total());
+
+    // a second enote overflows the balance once both are locked or both are unlocked
+    let coinbase_record = receive_coinbase(&bob, 1, 101);
+    assert!(enote_store.add_enote(coinbase_record, 101, None));
+    assert_eq!(None, enote_store.balance(130));
+    assert_eq!(None, enote_store.balance(200));
+    let balance = enote_store.balance(159).expect("balance");
+    assert_eq!(
+        CarrotBalanceV1 {
+            unlocked: Amount::MAX,
+            locked: 1
+        },
+        balance
+    );
+    assert_eq!(None, balance.total());
+}
+
+#[test]
+fn enote_store_view_only_spends() {
+    let bob: CarrotAccount = gen_random();
+    let mut subaddress_table = make_subaddress_table(&bob);
+    let mut enote_store = CarrotEnoteStore::new();
+
+    let record = receive_payment(&bob, &mut subaddress_table, bob.main_address(), 500);
+    let onetime_address = record.onetime_address().clone();
+    let key_image = key_image(&bob, &record);
+
+    // a view-only wallet can't see spends without key images...
+    assert!(enote_store.add_enote(record, 100, None));
+    assert_eq!(
+        0,
+        enote_store.process_spent_key_images(core::slice::from_ref(&key_image), 120)
+    );
+    assert_eq!(500, enote_store.balance(200).expect("balance").unlocked);
+
+    // ...until they're imported
+    assert!(enote_store.set_key_image(&onetime_address, key_image.clone()));
+    assert_eq!(
+        1,
+        enote_store.process_spent_key_images(core::slice::from_ref(&key_image), 120)
+    );
+    assert_eq!(Some(CarrotBalanceV1::default()), enote_store.balance(200));
+}
+
+#[test]
+fn enote_store_rollback() {
+    let bob: CarrotAccount = gen_random();
+    let mut subaddress_table = make_subaddress_table(&bob);
+    let mut enote_store = CarrotEnoteStore::new();
+
+    let old_record = receive_payment(&bob, &mut subaddress_table, bob.main_address(), 1000);
+    let old_key_image = key_image(&bob, &old_record);
+    let reorged_record = receive_payment(&bob, &mut subaddress_table, bob.main_address(), 200);
+    let reorged_onetime_address = reorged_record.onetime_address().clone();
+    let reorged_key_image = key_image(&bob, &reorged_record);
+    let coinbase_record = receive_coinbase(&bob, 30, 103);
+    let coinbase_key_image = key_image(&bob, &coinbase_record);
+
+    // a coinbase enote can only be added at the block index its input context commits to
+    assert!(!enote_store.add_enote(coinbase_record.clone(), 102, None));
+
+    assert!(enote_store.add_enote(old_record, 100, Some(old_key_image.clone())));
+    assert!(enote_store.add_enote(reorged_record, 102, Some(reorged_key_image)));
+    assert!(enote_store.add_enote(coinbase_record, 103, Some(coinbase_key_image)));
+    for block_index in 100..=103 {
+        enote_store.add_checkpoint(block_index, [block_index as u8; 32]);
+    }
+    assert_eq!(
+        1,
+        enote_store.process_spent_key_images(core::slice::from_ref(&old_key_image), 102)
+    );
+    assert_eq!(230, enote_store.balance(200).expect("balance").unlocked);
+
+    // blocks 102 and 103 are orphaned
+    assert_eq!(2, enote_store.rollback(101));
+    assert_eq!(1, enote_store.enotes().len());
+    assert!(enote_store.get(&reorged_onetime_address).is_none());
+    assert!(!enote_store.enotes()[0].is_spent());
+    assert_eq!(1000, enote_store.balance(200).expect("balance").unlocked);
+    assert_eq!(Some((101, &[101; 32])), enote_store.top_checkpoint());
+    assert!(enote_store.checkpoint(102).is_none());
+
+    // the spend lands in the new chain, and rolling back to its block keeps it
+    assert_eq!(
+        1,
+        enote_store.process_spent_key_images(core::slice::from_ref(&old_key_image), 103)
+    );
+    assert_eq!(0, enote_store.rollback(103));
+    assert!(enote_store.enotes()[0].is_spent());
+    assert_eq!(Some(CarrotBalanceV1::default()), enote_store.balance(200));
+}
+
+fn with_amount(record: &CarrotOwnedEnoteRecordV1, amount: Amount) -> CarrotOwnedEnoteRecordV1 {
+    let mut record = record.clone();
+    match &mut record {
+        CarrotOwnedEnoteRecordV1::External(r) => r.record.amount = amount,
+        _ => panic!("expected an external enote record"),
+    }
+    record
+}
+
+#[test]
+fn enote_store_burning_bug() {
+    let bob: CarrotAccount = gen_random();
+    let mut subaddress_table = make_subaddress_table(&bob);
+    let mut enote_store = CarrotEnoteStore::new();
+
+    let record = receive_payment(&bob, &mut subaddress_table, bob.main_address(), 1000);
+    let onetime_address = record.onetime_address().clone();
+    let key_image = key_image(&bob, &record);
+    assert!(enote_store.add_enote(record.clone(), 100, Some(key_image.clone())));
+
+    // re-adding the same enote, e.g. when rescanning, isn't a duplicate
+    assert!(!enote_store.add_enote(record.clone(), 100, None));
+    assert!(enote_store.burnt_enotes().is_empty());
+
+    // a smaller enote reusing K_o is burnt
+    assert!(!enote_store.add_enote(with_amount(&record, 5), 105, None));
+    assert_eq!(1, enote_store.burnt_enotes().len());
+    assert_eq!(1000, enote_store.balance(200).expect("balance").unlocked);
+
+    // a larger enote reusing K_o replaces the tracked one
+    assert!(enote_store.add_enote(with_amount(&record, 2000), 106, None));
+    assert_eq!(2, enote_store.burnt_enotes().len());
+    assert_eq!(1, enote_store.enotes().len());
+    assert_eq!(2000, enote_store.balance(200).expect("balance").unlocked);
+    assert_eq!(
+        Some(&key_image),
+        enote_store
+            .get(&onetime_address)
+            .unwrap()
+            .key_image
+            .as_ref()
+    );
+
+    // if the larger enote is orphaned, the original one is spendable again
+    assert_eq!(1, enote_store.rollback(105));
+    assert_eq!(1, enote_store.burnt_enotes().len());
+    assert_eq!(1000, enote_store.balance(200).expect("balance").unlocked);
+    assert_eq!(100, enote_store.get(&onetime_address).unwrap().block_index);
+
+    // spending the key image spends the tracked enote, and the balance never counts the others
+    assert_eq!(
+        1,
+        enote_store.process_spent_key_images(core::slice::from_ref(&key_image), 110)
+    );
+    assert!(!enote_store.add_enote(with_amount(&record, 3000), 111, None));
+    assert_eq!(Some(CarrotBalanceV1::default()), enote_store.balance(200));
+}
@@ -0,0 +1,201 @@
+#![cfg(feature = "alloc")]
+
+mod common;
+use crate::common::random::*;
+
+use carrot_crypto::fees::*;
+use carrot_crypto::output_set_finalization::AdditionalOutputType;
+use carrot_crypto::payments::*;
+use carrot_crypto::tx_builder::CarrotTxBuilderV1;
+use carrot_crypto::*;
+
+const FEE_MODEL: FeeModelV1 = FeeModelV1 {
+    fee_per_weight: 2,
+    base_weight: 10,
+    weight_per_input: 5,
+    weight_per_output: 3,
+};
+
+fn make_payment_proposal(amount: Amount) -> CarrotPaymentProposalV1 {
+    CarrotPaymentProposalV1 {
+        destination: gen_random::<CarrotAccount>().main_address(),
+        amount,
+        randomness: gen_random(),
+    }
+}
+
+fn make_selfsend_proposal(
+    account: &CarrotAccount,
+    amount: Amount,
+) -> CarrotPaymentProposalSelfSendV1 {
+    CarrotPaymentProposalSelfSendV1 {
+        destination_address_spend_pubkey: account.account_spend_pubkey().clone(),
+        amount,
+        enote_type: CarrotEnoteType::Change,
+        enote_ephemeral_pubkey: None,
+        internal_message: None,
+    }
+}
+
+#[test]
+fn fee_and_change_with_change_output() {
+    // 1 input, 1 payment and a shared change output: weight = 10 + 5 + 2 * 3 = 21
+    let tx_fee = get_tx_fee_and_change(&[1000], &[make_payment_proposal(500)], &[], &FEE_MODEL)
+        .expect("get_tx_fee_and_change");
+    assert_eq!(
+        CarrotTxFeeV1 {
+            fee: 42,
+            change_amount: 458,
+            additional_output_type: Some(AdditionalOutputType::ChangeShared),
+            num_outputs: 2,
+        },
+        tx_fee
+    );
+}
+
+#[test]
+fn fee_and_change_exact_balance() {
+    let alice: CarrotAccount = gen_random();
+    let normal_payment_proposals = [
+        make_payment_proposal(100),
+        make_payment_proposal(200),
+        make_payment_proposal(300),
+    ];
+    let selfsend_payment_proposals = [make_selfsend_proposal(&alice, 400)];
+
+    // 2 inputs and 4 outputs: weight = 10 + 2 * 5 + 4 * 3 = 32, so no change is needed
+    let tx_fee = get_tx_fee_and_change(
+        &[600, 464],
+        &normal_payment_proposals,
+        &selfsend_payment_proposals,
+        &FEE_MODEL,
+    )
+    .expect("get_tx_fee_and_change");
+    assert_eq!(
+        CarrotTxFeeV1 {
+            fee: 64,
+            change_amount: 0,
+            additional_output_type: None,
+            num_outputs: 4,
+        },
+        tx_fee
+    );
+
+    // a leftover amount which can't pay for a 5th output goes to the fee, up to and including
+    // the 5th output's weight: 3 * 2 = 6
+    for leftover_amount in [1, 5, 6] {
+        let tx_fee = get_tx_fee_and_change(
+            &[600, 464 + leftover_amount],
+            &normal_payment_proposals,
+            &selfsend_payment_proposals,
+            &FEE_MODEL,
+        )
+        .expect("get_tx_fee_and_change");
+        assert_eq!(
+            CarrotTxFeeV1 {
+                fee: 64 + leftover_amount,
+                change_amount: 0,
+                additional_output_type: None,
+                num_outputs: 4,
+            },
+            tx_fee
+        );
+    }
+
+    // past that, the leftover amount pays for a 5th output, and the rest is change
+    let tx_fee = get_tx_fee_and_change(
+        &[600, 471],
+        &normal_payment_proposals,
+        &selfsend_payment_proposals,
+        &FEE_MODEL,
+    )
+    .expect("get_tx_fee_and_change");
+    assert_eq!(
+        CarrotTxFeeV1 {
+            fee: 70,
+            change_amount: 1,
+            additional_output_type: Some(AdditionalOutputType::ChangeUnique),
+            num_outputs: 5,
+        },
+        tx_fee
+    );
+
+    // inputs which don't cover the payments and fee without a change output are insufficient
+    let error = get_tx_fee_and_change(
+        &[600, 463],
+        &normal_payment_proposals,
+        &selfsend_payment_proposals,
+        &FEE_MODEL,
+    )
+    .expect_err("get_tx_fee_and_change should fail");
+    assert_eq!(ErrorKind::InsufficientFunds, error.kind());
+    let tx_fee = get_tx_fee_and_change(
+        &[600, 564],
+        &normal_payment_proposals,
+        &selfsend_payment_proposals,
+        &FEE_MODEL,
+    )
+    .expect("get_tx_fee_and_change");
+    assert_eq!(
+        CarrotTxFeeV1 {
+            fee: 70,
+            change_amount: 94,
+            additional_output_type: Some(AdditionalOutputType::ChangeUnique),
+            num_outputs: 5,
+        },
+        tx_fee
+    );
+}
+
+#[test]
+fn fee_and_change_overflow() {
+    let error = get_tx_fee_and_change(
+        &[Amount::MAX, 1],
+        &[make_payment_proposal(500)],
+        &[],
+        &FEE_MODEL,
+    )
+    .expect_err("get_tx_fee_and_change should fail");
+    assert_eq!(ErrorKind::AmountOverflow, error.kind());
+
+    let error = get_tx_fee_and_change(
+        &[1000],
+        &[make_payment_proposal(Amount::MAX), make_payment_proposal(1)],
+        &[],
+        &FEE_MODEL,
+    )
+    .expect_err("get_tx_fee_and_change should fail");
+    assert_eq!(ErrorKind::AmountOverflow, error.kind());
+
+    let expensive_fee_model = FeeModelV1 {
+        fee_per_weight: Amount::MAX,
+        ..FEE_MODEL
+    };
+    assert_eq!(
+        ErrorKind::AmountOverflow,
+        expensive_fee_model.estimate_fee(1, 2).unwrap_err().kind()
+    );
+}
+
+#[test]
+fn tx_amounts_balance() {
+    let alice: CarrotAccount = gen_random();
+    let bob: CarrotAccount = gen_random();
+
+    let tx_proposal = CarrotTxBuilderV1::new(alice.account_spend_pubkey().clone(), 2000)
+        .with_recipient(bob.main_address(), 1000)
+        .with_fee(10)
+        .build(
+            gen_random(),
+            Some(&alice),
+            Some(&alice),
+            &mut rand_core::OsRng,
+        )
+        .expect("build");
+
+    check_tx_amounts_balance(&[1500, 500], &tx_proposal.output_enote_proposals, 10)
+        .expect("check_tx_amounts_balance");
+    let error = check_tx_amounts_balance(&[1500, 500], &tx_proposal.output_enote_proposals, 11)
+        .expect_err("check_tx_amounts_balance should fail");
+    assert_eq!(ErrorKind::UnbalancedAmounts, error.kind());
+}
@@ -0,0 +1,123 @@
+#![cfg(feature = "alloc")]
+
+mod common;
+use crate::common::keys::{key_image, receive_coinbase, receive_tx_proposal};
+use crate::common::random::*;
+
+use carrot_crypto::enote_store::*;
+use carrot_crypto::input_selection::*;
+use carrot_crypto::payments::ErrorKind;
+use carrot_crypto::tx_builder::CarrotTxBuilderV1;
+use carrot_crypto::*;
+
+// bob pays alice and himself the same amount, keeping the change
+fn receive_tx(
+    bob: &CarrotAccount,
+    alice: &CarrotAccount,
+    payment_amount: Amount,
+    change_amount: Amount,
+) -> Vec<CarrotOwnedEnoteRecordV1> {
+    let tx_proposal = CarrotTxBuilderV1::new(
+        bob.account_spend_pubkey().clone(),
+        2 * payment_amount + change_amount,
+    )
+    .with_recipient(alice.main_address(), payment_amount)
+    .with_recipient(bob.main_address(), payment_amount)
+    .build(gen_random(), Some(bob), Some(bob), &mut rand_core::OsRng)
+    .expect("build");
+    receive_tx_proposal(bob, &tx_proposal)
+}
+
+fn add_enotes(
+    enote_store: &mut CarrotEnoteStore,
+    account: &CarrotAccount,
+    records: Vec<CarrotOwnedEnoteRecordV1>,
+    block_index: BlockIndex,
+) {
+    for record in records {
+        let key_image = key_image(account, &record);
+        assert!(enote_store.add_enote(record, block_index, Some(key_image)));
+    }
+}
+
+#[test]
+fn select_inputs_from_enote_store() {
+    let alice: CarrotAccount = gen_random();
+    let bob: CarrotAccount = gen_random();
+    let mut enote_store = CarrotEnoteStore::new();
+
+    // payments of 100 and 200 to himself with change of 40 and 70, and a coinbase of 1000
+    add_enotes(
+        &mut enote_store,
+        &bob,
+        receive_tx(&bob, &alice, 100, 40),
+        100,
+    );
+    add_enotes(
+        &mut enote_store,
+        &bob,
+        receive_tx(&bob, &alice, 200, 70),
+        100,
+    );
+    add_enotes(
+        &mut enote_store,
+        &bob,
+        vec![receive_coinbase(&bob, 1000, 100)],
+        100,
+    );
+    assert_eq!(5, enote_store.enotes().len());
+
+    // nothing is unlocked yet, and then the coinbase enote is locked for longer
+    let error = select_inputs(&enote_store, 108, 1, None, &GreedyInputSelector).unwrap_err();
+    assert_eq!(ErrorKind::InsufficientFunds, error.kind());
+    let selection =
+        select_inputs(&enote_store, 110, 410, None, &GreedyInputSelector).expect("select_inputs");
+    assert_eq!(4, selection.inputs.len());
+    assert_eq!(410, selection.input_amount);
+    let selection =
+        select_inputs(&enote_store, 159, 1000, None, &GreedyInputSelector).expect("select_inputs");
+    assert_eq!(1, selection.inputs.len());
+    assert!(selection.inputs[0].record.is_coinbase());
+
+    // only consolidate change
+    let selection = select_inputs(
+        &enote_store,
+        159,
+        100,
+        Some(CarrotEnoteType::Change),
+        &MinChangeInputSelector,
+    )
+    .expect("select_inputs");
+    assert_eq!(110, selection.input_amount);
+    for input in selection.inputs.iter() {
+        assert_eq!(CarrotEnoteType::Change, input.record.enote_type());
+    }
+
+    // the first input's key image makes the input context of the new tx
+    assert_eq!(2, selection.inputs.len());
+    let first_key_image = selection.first_key_image().expect("first_key_image");
+    assert_eq!(
+        Some(InputContext::new_ringct(first_key_image)),
+        selection.input_context()
+    );
+
+    // spent enotes aren't selected again
+    let spent_key_images: Vec<KeyImage> = selection
+        .inputs
+        .iter()
+        .map(|input| input.key_image.clone().unwrap())
+        .collect();
+    assert_eq!(
+        2,
+        enote_store.process_spent_key_images(&spent_key_images, 160)
+    );
+    let error = select_inputs(
+        &enote_store,
+        170,
+        1,
+        Some(CarrotEnoteType::Change),
+        &GreedyInputSelector,
+    )
+    .unwrap_err();
+    assert_eq!(ErrorKind::InsufficientFunds, error.kind());
+}
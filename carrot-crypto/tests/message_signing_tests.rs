@@ -0,0 +1,73 @@
+mod common;
+use crate::common::random::*;
+
+use carrot_crypto::message_signing::*;
+use carrot_crypto::*;
+
+const MESSAGE: &[u8] = b"I control this address";
+
+#[test]
+fn message_signature_roundtrip() {
+    let alice: CarrotAccount = gen_random();
+    let index = gen_subaddress_index();
+    for (major_index, minor_index) in [(0, 0), (index.major, index.minor)] {
+        let destination = alice
+            .subaddress(major_index, minor_index)
+            .expect("subaddress");
+        let signature = CarrotMessageSignatureV1::sign(
+            &alice,
+            major_index,
+            minor_index,
+            MESSAGE,
+            &mut rand_core::OsRng,
+        )
+        .expect("sign");
+        assert!(signature.verify(&destination, MESSAGE));
+        assert_eq!(
+            Some(signature.clone()),
+            CarrotMessageSignatureV1::from_bytes(&signature.to_bytes())
+        );
+    }
+
+    // main address signatures also verify with integrated addresses
+    let signature =
+        CarrotMessageSignatureV1::sign(&alice, 0, 0, MESSAGE, &mut rand_core::OsRng).expect("sign");
+    assert!(signature.verify(
+        &alice.integrated_address(gen_non_null_payment_id()),
+        MESSAGE
+    ));
+}
+
+#[test]
+fn message_signature_rejects() {
+    let alice: CarrotAccount = gen_random();
+    let bob: CarrotAccount = gen_random();
+    let destination = alice.subaddress(1, 2).expect("subaddress");
+    let signature =
+        CarrotMessageSignatureV1::sign(&alice, 1, 2, MESSAGE, &mut rand_core::OsRng).expect("sign");
+
+    // another message or address
+    assert!(!signature.verify(&destination, b"I control that address"));
+    assert!(!signature.verify(&alice.subaddress(1, 3).expect("subaddress"), MESSAGE));
+    assert!(!signature.verify(&alice.main_address(), MESSAGE));
+    assert!(!signature.verify(&bob.subaddress(1, 2).expect("subaddress"), MESSAGE));
+
+    // the view pubkey is signed too
+    let mut bad_destination = destination.clone();
+    bad_destination.address_view_pubkey = bob
+        .subaddress(1, 2)
+        .expect("subaddress")
+        .address_view_pubkey;
+    assert!(!signature.verify(&bad_destination, MESSAGE));
+
+    // tampered signature
+    let mut bad_signature = signature.clone();
+    bad_signature.response_t += curve25519_dalek::Scalar::ONE;
+    assert!(!bad_signature.verify(&destination, MESSAGE));
+    let mut bad_signature_bytes = signature.to_bytes();
+    bad_signature_bytes[31] = 0xff;
+    assert_eq!(
+        None,
+        CarrotMessageSignatureV1::from_bytes(&bad_signature_bytes)
+    );
+}
@@ -0,0 +1,222 @@
+mod common;
+use crate::common::random::*;
+
+use carrot_crypto::output_set_finalization::*;
+use carrot_crypto::payments::*;
+use carrot_crypto::*;
+
+fn make_payment_proposal(destination: CarrotDestinationV1) -> CarrotPaymentProposalV1 {
+    CarrotPaymentProposalV1 {
+        destination,
+        amount: gen_random(),
+        randomness: gen_random(),
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn output_enote_proposals_vec_order() {
+    let alice: CarrotAccount = gen_random();
+
+    let normal_payment_proposals: Vec<CarrotPaymentProposalV1> = (0..4)
+        .map(|_| make_payment_proposal(gen_random::<CarrotAccount>().main_address()))
+        .collect();
+    let selfsend_payment_proposals: Vec<CarrotPaymentProposalSelfSendV1> = (0..2)
+        .map(|_| CarrotPaymentProposalSelfSendV1 {
+            destination_address_spend_pubkey: alice.account_spend_pubkey().clone(),
+            amount: gen_random(),
+            enote_type: CarrotEnoteType::Change,
+            enote_ephemeral_pubkey: Some(gen_random()),
+            internal_message: None,
+        })
+        .collect();
+    let dummy_encrypted_payment_id: EncryptedPaymentId = gen_random();
+
+    let (output_enote_proposals, encrypted_payment_id, payment_proposal_order) =
+        get_output_enote_proposals_vec(
+            &normal_payment_proposals,
+            &selfsend_payment_proposals,
+            &Some(dummy_encrypted_payment_id.clone()),
+            Some(&alice),
+            Some(&alice),
+            gen_random(),
+        )
+        .expect("get_output_enote_proposals_vec");
+
+    assert_eq!(dummy_encrypted_payment_id, encrypted_payment_id);
+    assert_eq!(6, output_enote_proposals.len());
+    assert_eq!(6, payment_proposal_order.len());
+
+    // each output enote proposal maps back to a distinct proposal with its amount
+    for (output_enote_proposal, order) in output_enote_proposals
+        .iter()
+        .zip(payment_proposal_order.iter())
+    {
+        let amount = match order {
+            PaymentProposalOrder::Normal(i) => normal_payment_proposals[*i].amount,
+            PaymentProposalOrder::SelfSend(i) => selfsend_payment_proposals[*i].amount,
+        };
+        assert_eq!(amount, output_enote_proposal.amount);
+    }
+    for i in 0..payment_proposal_order.len() {
+        for j in (i + 1)..payment_proposal_order.len() {
+            assert_ne!(payment_proposal_order[i], payment_proposal_order[j]);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn coinbase_output_enotes_vec_order() {
+    let normal_payment_proposals: Vec<CarrotPaymentProposalV1> = (0..8)
+        .map(|_| make_payment_proposal(gen_random::<CarrotAccount>().main_address()))
+        .collect();
+
+    let (output_coinbase_enotes, payment_proposal_order) =
+        get_coinbase_output_enotes_vec(&normal_payment_proposals, 1234)
+            .expect("get_coinbase_output_enotes_vec");
+
+    assert_eq!(8, output_coinbase_enotes.len());
+    let mut sorted_order = payment_proposal_order.clone();
+    sorted_order.sort();
+    assert_eq!((0..8).collect::<Vec<usize>>(), sorted_order);
+
+    // the enotes are reordered along with the proposal order
+    for (coinbase_enote, i) in output_coinbase_enotes
+        .iter()
+        .zip(payment_proposal_order.iter())
+    {
+        assert_eq!(normal_payment_proposals[*i].amount, coinbase_enote.amount);
+        assert_eq!(1234, coinbase_enote.block_index);
+    }
+}
+
+#[test]
+fn coinbase_output_enotes_order() {
+    let normal_payment_proposals: Vec<CarrotPaymentProposalV1> = (0..8)
+        .map(|_| make_payment_proposal(gen_random::<CarrotAccount>().main_address()))
+        .collect();
+    let mut output_coinbase_enotes: [CarrotCoinbaseEnoteV1; 8] =
+        core::array::from_fn(|_| CarrotCoinbaseEnoteV1 {
+            onetime_address: Default::default(),
+            amount: 0,
+            anchor_enc: Default::default(),
+            view_tag: Default::default(),
+            enote_ephemeral_pubkey: Default::default(),
+            block_index: 0,
+        });
+    let mut payment_proposal_order = [0usize; 8];
+
+    get_coinbase_output_enotes(
+        &normal_payment_proposals,
+        1234,
+        &mut output_coinbase_enotes,
+        &mut payment_proposal_order,
+    )
+    .expect("get_coinbase_output_enotes");
+
+    // the enotes pass the K_o sorting check, and are reordered along with the proposal order
+    for (coinbase_enote, i) in output_coinbase_enotes
+        .iter()
+        .zip(payment_proposal_order.iter())
+    {
+        assert_eq!(
+            normal_payment_proposals[*i]
+                .get_coinbase_output_proposal(1234)
+                .expect("get_coinbase_output_proposal"),
+            *coinbase_enote
+        );
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn additional_change_unique_output() {
+    let alice: CarrotAccount = gen_random();
+
+    // 2 outgoing payments and change needs a self-send with its own D_e
+    let normal_payment_proposals: Vec<CarrotPaymentProposalV1> = (0..2)
+        .map(|_| make_payment_proposal(gen_random::<CarrotAccount>().main_address()))
+        .collect();
+    let AdditionalOutputProposal::Selfsend(change_proposal) = get_additional_output_proposal(
+        2,
+        0,
+        500,
+        false,
+        alice.account_spend_pubkey(),
+        &mut rand_core::OsRng,
+    ) else {
+        panic!("expected a self-send additional output proposal");
+    };
+    assert_eq!(CarrotEnoteType::Change, change_proposal.enote_type);
+    assert!(change_proposal.enote_ephemeral_pubkey.is_some());
+
+    let (output_enote_proposals, _, _) = get_output_enote_proposals_vec(
+        &normal_payment_proposals,
+        &[change_proposal],
+        &Some(gen_random()),
+        Some(&alice),
+        Some(&alice),
+        gen_random(),
+    )
+    .expect("get_output_enote_proposals_vec");
+    assert_eq!(3, output_enote_proposals.len());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn output_enote_proposals_selfsend_order() {
+    let alice: CarrotAccount = gen_random();
+
+    // self-send indices 0 and 1 overlap the normal proposal indices
+    let normal_payment_proposals: Vec<CarrotPaymentProposalV1> = (0..2)
+        .map(|_| make_payment_proposal(gen_random::<CarrotAccount>().main_address()))
+        .collect();
+    let selfsend_payment_proposals: Vec<CarrotPaymentProposalSelfSendV1> =
+        [CarrotEnoteType::Payment, CarrotEnoteType::Change]
+            .into_iter()
+            .map(|enote_type| CarrotPaymentProposalSelfSendV1 {
+                destination_address_spend_pubkey: alice.account_spend_pubkey().clone(),
+                amount: gen_random(),
+                enote_type,
+                enote_ephemeral_pubkey: Some(gen_random()),
+                internal_message: None,
+            })
+            .collect();
+
+    let (output_enote_proposals, _, payment_proposal_order) = get_output_enote_proposals_vec(
+        &normal_payment_proposals,
+        &selfsend_payment_proposals,
+        &Some(gen_random()),
+        Some(&alice),
+        Some(&alice),
+        gen_random(),
+    )
+    .expect("get_output_enote_proposals_vec");
+
+    // every proposal shows up exactly once, next to the enote made from it
+    let mut sorted_order = payment_proposal_order.clone();
+    sorted_order.sort_by_key(|order| match order {
+        PaymentProposalOrder::Normal(i) => *i,
+        PaymentProposalOrder::SelfSend(i) => 2 + *i,
+    });
+    assert_eq!(
+        vec![
+            PaymentProposalOrder::Normal(0),
+            PaymentProposalOrder::Normal(1),
+            PaymentProposalOrder::SelfSend(0),
+            PaymentProposalOrder::SelfSend(1),
+        ],
+        sorted_order
+    );
+    for (output_enote_proposal, order) in output_enote_proposals
+        .iter()
+        .zip(payment_proposal_order.iter())
+    {
+        let amount = match order {
+            PaymentProposalOrder::Normal(i) => normal_payment_proposals[*i].amount,
+            PaymentProposalOrder::SelfSend(i) => selfsend_payment_proposals[*i].amount,
+        };
+        assert_eq!(amount, output_enote_proposal.amount);
+    }
+}
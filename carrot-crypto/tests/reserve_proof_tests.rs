@@ -0,0 +1,183 @@
+#![cfg(feature = "alloc")]
+
+mod common;
+use crate::common::keys::{receive_coinbase, receive_tx_proposal};
+use crate::common::random::*;
+
+use carrot_crypto::reserve_proofs::*;
+use carrot_crypto::tx_builder::CarrotTxBuilderV1;
+use carrot_crypto::tx_proofs::ErrorKind;
+use carrot_crypto::*;
+
+const MESSAGE: &[u8] = b"proof of reserves 2026-10";
+
+// alice pays bob's subaddress and herself, keeping the change, and both scan the tx
+fn scan_tx(
+    alice: &CarrotAccount,
+    bob: &CarrotAccount,
+) -> (Vec<CarrotOwnedEnoteRecordV1>, Vec<CarrotOwnedEnoteRecordV1>) {
+    let tx_proposal = CarrotTxBuilderV1::new(alice.account_spend_pubkey().clone(), 1000)
+        .with_recipient(bob.subaddress(1, 2).expect("subaddress"), 300)
+        .with_recipient(alice.main_address(), 200)
+        .build(
+            gen_random(),
+            Some(alice),
+            Some(alice),
+            &mut rand_core::OsRng,
+        )
+        .expect("build");
+    (
+        receive_tx_proposal(alice, &tx_proposal),
+        receive_tx_proposal(bob, &tx_proposal),
+    )
+}
+
+#[test]
+fn reserve_proof_roundtrip() {
+    let alice: CarrotAccount = gen_random();
+    let bob: CarrotAccount = gen_random();
+
+    // alice owns her payment to herself, her change and a coinbase enote
+    let (mut records, bob_records) = scan_tx(&alice, &bob);
+    assert_eq!(2, records.len());
+    assert_eq!(1, bob_records.len());
+    records.push(receive_coinbase(&alice, 5000, gen_block_index()));
+    let total_amount: Amount = records.iter().map(|record| record.amount()).sum();
+    assert_eq!(5000 + 1000 - 300, total_amount);
+
+    let reserve_proof =
+        CarrotReserveProofV1::prove(&alice, &records, MESSAGE, &mut rand_core::OsRng)
+            .expect("prove");
+    assert_eq!(
+        Ok(total_amount),
+        reserve_proof.verify(MESSAGE).map_err(|e| e.kind())
+    );
+    for (key_image, record) in reserve_proof.key_images().zip(records.iter()) {
+        assert_eq!(
+            Some(key_image),
+            alice.enote_record_key_image(record).as_ref()
+        );
+    }
+
+    // bob's enote was received to a subaddress
+    let reserve_proof =
+        CarrotReserveProofV1::prove(&bob, &bob_records, MESSAGE, &mut rand_core::OsRng)
+            .expect("prove");
+    assert_eq!(300, reserve_proof.verify(MESSAGE).expect("verify"));
+
+    // an empty reserve proof proves nothing
+    let reserve_proof =
+        CarrotReserveProofV1::prove(&bob, &[], MESSAGE, &mut rand_core::OsRng).expect("prove");
+    assert_eq!(0, reserve_proof.verify(MESSAGE).expect("verify"));
+}
+
+#[test]
+fn open_enote_records() {
+    let alice: CarrotAccount = gen_random();
+    let bob: CarrotAccount = gen_random();
+    let (records, bob_records) = scan_tx(&alice, &bob);
+
+    for record in records.iter() {
+        // K_o = x G + y T
+        let (x, y) = alice
+            .try_open_enote_record(record)
+            .expect("try_open_enote_record");
+        assert_eq!(
+            record.onetime_address(),
+            &OutputPubkey::from(opening::OpenedPoint::scalar_mul_gt(&x, &y))
+        );
+        assert!(bob.try_open_enote_record(record).is_none());
+
+        // a Carrot-derived account can't open enotes received to legacy addresses, and vice versa
+        let legacy_alice =
+            legacy::LegacyAccount::new(gen_random(), alice.view_incoming_key().clone());
+        assert!(legacy_alice.try_open_enote_record(record).is_none());
+        assert!(legacy_alice.enote_record_key_image(record).is_none());
+        let mut legacy_record = record.clone();
+        match &mut legacy_record {
+            CarrotOwnedEnoteRecordV1::Coinbase(r) => {
+                r.subaddress_index.derive_type = AddressDeriveType::Legacy
+            }
+            CarrotOwnedEnoteRecordV1::External(r) => {
+                r.subaddress_index.derive_type = AddressDeriveType::Legacy
+            }
+            CarrotOwnedEnoteRecordV1::Internal(r) => {
+                r.subaddress_index.derive_type = AddressDeriveType::Legacy
+            }
+        }
+        assert!(alice.try_open_enote_record(&legacy_record).is_none());
+        assert!(alice.enote_record_key_image(&legacy_record).is_none());
+    }
+    assert!(bob.try_open_enote_record(&bob_records[0]).is_some());
+}
+
+#[test]
+fn reserve_proof_rejects() {
+    let alice: CarrotAccount = gen_random();
+    let bob: CarrotAccount = gen_random();
+    let (records, bob_records) = scan_tx(&alice, &bob);
+
+    // bob can't open alice's enotes
+    let error = CarrotReserveProofV1::prove(&bob, &records, MESSAGE, &mut rand_core::OsRng)
+        .expect_err("prove should fail");
+    assert_eq!(ErrorKind::OpeningFailed, error.kind());
+
+    let reserve_proof =
+        CarrotReserveProofV1::prove(&alice, &records, MESSAGE, &mut rand_core::OsRng)
+            .expect("prove");
+
+    // another message
+    let error = reserve_proof
+        .verify(b"proof of reserves 2026-11")
+        .expect_err("verify should fail");
+    assert_eq!(ErrorKind::InvalidProof, error.kind());
+
+    // inflated amount
+    let mut bad_reserve_proof = reserve_proof.clone();
+    bad_reserve_proof.entries[0].amount += 1;
+    let error = bad_reserve_proof
+        .verify(MESSAGE)
+        .expect_err("verify should fail");
+    assert_eq!(ErrorKind::AmountCommitmentMismatch, error.kind());
+
+    // the same enote counted twice
+    let mut bad_reserve_proof = reserve_proof.clone();
+    bad_reserve_proof
+        .entries
+        .push(reserve_proof.entries[0].clone());
+    let error = bad_reserve_proof
+        .verify(MESSAGE)
+        .expect_err("verify should fail");
+    assert_eq!(ErrorKind::DuplicateEnote, error.kind());
+
+    // a fake key image, which would never show up as spent
+    let mut bad_reserve_proof = reserve_proof.clone();
+    bad_reserve_proof.entries[0].key_image = gen_random();
+    let error = bad_reserve_proof
+        .verify(MESSAGE)
+        .expect_err("verify should fail");
+    assert_eq!(ErrorKind::InvalidProof, error.kind());
+
+    // a one-time address with a torsion component
+    let mut bad_reserve_proof = reserve_proof.clone();
+    let torsioned_onetime_address = curve25519_dalek::EdwardsPoint::mul_base(&gen_random())
+        + curve25519_dalek::constants::EIGHT_TORSION[1];
+    bad_reserve_proof.entries[0].onetime_address =
+        OutputPubkey::from_bytes(torsioned_onetime_address.compress().0);
+    let error = bad_reserve_proof
+        .verify(MESSAGE)
+        .expect_err("verify should fail");
+    assert_eq!(ErrorKind::InvalidPoint, error.kind());
+
+    // someone else's enote
+    let bob_reserve_proof =
+        CarrotReserveProofV1::prove(&bob, &bob_records, MESSAGE, &mut rand_core::OsRng)
+            .expect("prove");
+    let mut bad_reserve_proof = reserve_proof.clone();
+    bad_reserve_proof.entries[0].onetime_address =
+        bob_reserve_proof.entries[0].onetime_address.clone();
+    let error = bad_reserve_proof
+        .verify(MESSAGE)
+        .expect_err("verify should fail");
+    assert_eq!(ErrorKind::InvalidProof, error.kind());
+}
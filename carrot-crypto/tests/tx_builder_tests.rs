@@ -0,0 +1,142 @@
+#![cfg(feature = "alloc")]
+
+mod common;
+use crate::common::keys::scan_tx_proposal;
+use crate::common::random::*;
+
+use carrot_crypto::payments::ErrorKind;
+use carrot_crypto::tx_builder::*;
+use carrot_crypto::*;
+
+#[test]
+fn tx_builder_multiple_recipients() {
+    let alice: CarrotAccount = gen_random();
+    let bob: CarrotAccount = gen_random();
+    let carol: CarrotAccount = gen_random();
+
+    let tx_builder = CarrotTxBuilderV1::new(alice.account_spend_pubkey().clone(), 2000)
+        .with_recipient(bob.main_address(), 1000)
+        .with_recipient(carol.subaddress(1, 2).expect("subaddress"), 500)
+        .with_fee(10);
+    assert_eq!(490, tx_builder.change_amount().expect("change_amount"));
+
+    let tx_proposal = tx_builder
+        .build(
+            gen_random(),
+            Some(&alice),
+            Some(&alice),
+            &mut rand_core::OsRng,
+        )
+        .expect("build");
+
+    // 2 payments and a change output
+    assert_eq!(3, tx_proposal.output_enote_proposals.len());
+    assert_eq!(3, tx_proposal.recipient_indices.len());
+
+    // the recipient mapping points each payment output back to its recipient
+    let mut change_output_index = None;
+    for (output_index, recipient_index) in tx_proposal.recipient_indices.iter().enumerate() {
+        let output_amount = tx_proposal.output_enote_proposals[output_index].amount;
+        match recipient_index {
+            Some(recipient_index) => {
+                assert_eq!(tx_builder.recipients()[*recipient_index].1, output_amount)
+            }
+            None => {
+                assert!(change_output_index.is_none());
+                assert_eq!(490, output_amount);
+                change_output_index = Some(output_index);
+            }
+        }
+    }
+
+    // each party finds their output
+    for (account, recipient_index) in [(&bob, 0), (&carol, 1)] {
+        let scan_result = scan_tx_proposal(account, &tx_proposal);
+        assert_eq!(1, scan_result.owned_enotes.len());
+        let output_index = scan_result.owned_enotes[0].output_index;
+        assert_eq!(
+            Some(recipient_index),
+            tx_proposal.recipient_indices[output_index]
+        );
+    }
+    let alice_scan_result = scan_tx_proposal(&alice, &tx_proposal);
+    assert_eq!(1, alice_scan_result.owned_enotes.len());
+    assert_eq!(
+        change_output_index,
+        Some(alice_scan_result.owned_enotes[0].output_index)
+    );
+    assert!(matches!(
+        alice_scan_result.owned_enotes[0].record,
+        CarrotOwnedEnoteRecordV1::Internal(_)
+    ));
+}
+
+#[test]
+fn tx_builder_single_recipient() {
+    let alice: CarrotAccount = gen_random();
+    let bob: CarrotAccount = gen_random();
+    let payment_id = gen_non_null_payment_id();
+
+    let tx_proposal = CarrotTxBuilderV1::new(alice.account_spend_pubkey().clone(), 100)
+        .with_recipient(bob.integrated_address(payment_id.clone()), 100)
+        .build::<CarrotAccount, _, _>(gen_random(), None, Some(&alice), &mut rand_core::OsRng)
+        .expect("build");
+
+    // a 2-out tx shares D_e between the payment and the zero-amount change
+    assert_eq!(2, tx_proposal.output_enote_proposals.len());
+    assert_eq!(
+        tx_proposal.output_enote_proposals[0]
+            .enote
+            .enote_ephemeral_pubkey,
+        tx_proposal.output_enote_proposals[1]
+            .enote
+            .enote_ephemeral_pubkey
+    );
+
+    let bob_scan_result = scan_tx_proposal(&bob, &tx_proposal);
+    assert_eq!(1, bob_scan_result.owned_enotes.len());
+    let CarrotOwnedEnoteRecordV1::External(bob_record) = &bob_scan_result.owned_enotes[0].record
+    else {
+        panic!("bob's payment should be found by the external scan path");
+    };
+    assert_eq!(payment_id, bob_record.record.payment_id);
+    assert_eq!(100, bob_record.record.amount);
+
+    // without a view-balance secret, the change is a special enote
+    let alice_scan_result = scan_tx_proposal(&alice, &tx_proposal);
+    assert_eq!(1, alice_scan_result.owned_enotes.len());
+    assert!(matches!(
+        alice_scan_result.owned_enotes[0].record,
+        CarrotOwnedEnoteRecordV1::External(_)
+    ));
+    assert_eq!(0, alice_scan_result.owned_enotes[0].record.amount());
+}
+
+#[test]
+fn tx_builder_insufficient_funds() {
+    let alice: CarrotAccount = gen_random();
+    let bob: CarrotAccount = gen_random();
+
+    let tx_builder = CarrotTxBuilderV1::new(alice.account_spend_pubkey().clone(), 1000)
+        .with_recipient(bob.main_address(), 1000)
+        .with_fee(1);
+    let error = tx_builder
+        .build(
+            gen_random(),
+            Some(&alice),
+            Some(&alice),
+            &mut rand_core::OsRng,
+        )
+        .err()
+        .expect("build should fail");
+    assert_eq!(ErrorKind::InsufficientFunds, error.kind());
+
+    // the sum of the outputs overflowing is reported as such, not as insufficient funds
+    let tx_builder = CarrotTxBuilderV1::new(alice.account_spend_pubkey().clone(), 1000)
+        .with_recipient(bob.main_address(), Amount::MAX)
+        .with_recipient(bob.main_address(), 2);
+    assert_eq!(
+        ErrorKind::AmountOverflow,
+        tx_builder.change_amount().unwrap_err().kind()
+    );
+}
@@ -0,0 +1,300 @@
+mod common;
+use crate::common::random::*;
+
+use carrot_crypto::payments::CarrotPaymentProposalV1;
+use carrot_crypto::tx_proofs::*;
+use carrot_crypto::*;
+
+const MESSAGE: &[u8] = b"order #1234";
+
+// send an enote to the destination, returning it with its encrypted pid and ephemeral privkey
+fn send_enote(
+    destination: &CarrotDestinationV1,
+    amount: Amount,
+) -> (CarrotEnoteV1, EncryptedPaymentId, EnoteEphemeralKey) {
+    let payment_proposal = CarrotPaymentProposalV1 {
+        destination: destination.clone(),
+        amount,
+        randomness: gen_random(),
+    };
+    let tx_first_key_image: KeyImage = gen_random();
+    let (output_enote_proposal, encrypted_payment_id) = payment_proposal
+        .get_normal_output_proposal(tx_first_key_image.clone())
+        .expect("get_normal_output_proposal");
+    let enote_ephemeral_privkey = EnoteEphemeralKey::derive(
+        &payment_proposal.randomness,
+        &InputContext::new_ringct(&tx_first_key_image),
+        &destination.address_spend_pubkey,
+        &destination.payment_id,
+    );
+    (
+        output_enote_proposal.enote,
+        encrypted_payment_id,
+        enote_ephemeral_privkey,
+    )
+}
+
+#[test]
+fn out_proof_roundtrip() {
+    let bob: CarrotAccount = gen_random();
+    let index = gen_subaddress_index();
+    let destinations = [
+        bob.main_address(),
+        bob.subaddress(index.major, index.minor)
+            .expect("subaddress"),
+        bob.integrated_address(gen_non_null_payment_id()),
+    ];
+
+    // repeat so that both signs of K_e are hit
+    for _ in 0..4 {
+        for destination in destinations.iter() {
+            let (enote, encrypted_payment_id, enote_ephemeral_privkey) =
+                send_enote(destination, 1234);
+            let out_proof = CarrotOutProofV1::prove(
+                &enote,
+                destination,
+                &enote_ephemeral_privkey,
+                MESSAGE,
+                &mut rand_core::OsRng,
+            )
+            .expect("prove");
+
+            let record = out_proof
+                .verify(&enote, Some(&encrypted_payment_id), destination, MESSAGE)
+                .expect("verify");
+            assert_eq!(1234, record.amount);
+            assert_eq!(
+                destination.address_spend_pubkey,
+                record.address_spend_pubkey
+            );
+            assert_eq!(destination.payment_id, record.payment_id);
+
+            let out_proof_bytes = out_proof.to_bytes();
+            assert_eq!(
+                Some(out_proof),
+                CarrotOutProofV1::from_bytes(&out_proof_bytes)
+            );
+        }
+    }
+}
+
+#[test]
+fn out_proof_rejects() {
+    let bob: CarrotAccount = gen_random();
+    let carol: CarrotAccount = gen_random();
+    let destination = bob.main_address();
+    let (enote, encrypted_payment_id, enote_ephemeral_privkey) = send_enote(&destination, 1234);
+
+    // the ephemeral key has to make the enote's D_e
+    let carol_subaddress = carol.subaddress(1, 1).expect("subaddress");
+    let error = CarrotOutProofV1::prove(
+        &enote,
+        &carol_subaddress,
+        &enote_ephemeral_privkey,
+        MESSAGE,
+        &mut rand_core::OsRng,
+    )
+    .expect_err("prove should fail");
+    assert_eq!(ErrorKind::EphemeralKeyMismatch, error.kind());
+    let error = CarrotOutProofV1::prove(
+        &enote,
+        &destination,
+        &gen_random(),
+        MESSAGE,
+        &mut rand_core::OsRng,
+    )
+    .expect_err("prove should fail");
+    assert_eq!(ErrorKind::EphemeralKeyMismatch, error.kind());
+
+    let out_proof = CarrotOutProofV1::prove(
+        &enote,
+        &destination,
+        &enote_ephemeral_privkey,
+        MESSAGE,
+        &mut rand_core::OsRng,
+    )
+    .expect("prove");
+
+    // D_e = d_e G for every main address, but the enote doesn't pay carol's
+    let carol_out_proof = CarrotOutProofV1::prove(
+        &enote,
+        &carol.main_address(),
+        &enote_ephemeral_privkey,
+        MESSAGE,
+        &mut rand_core::OsRng,
+    )
+    .expect("prove");
+    let error = carol_out_proof
+        .verify(
+            &enote,
+            Some(&encrypted_payment_id),
+            &carol.main_address(),
+            MESSAGE,
+        )
+        .expect_err("verify should fail");
+    assert!(matches!(error.kind(), ErrorKind::ScanFailed(_)));
+
+    // another message or destination
+    let error = out_proof
+        .verify(
+            &enote,
+            Some(&encrypted_payment_id),
+            &destination,
+            b"order #1235",
+        )
+        .expect_err("verify should fail");
+    assert_eq!(ErrorKind::InvalidProof, error.kind());
+    let error = out_proof
+        .verify(
+            &enote,
+            Some(&encrypted_payment_id),
+            &carol.main_address(),
+            MESSAGE,
+        )
+        .expect_err("verify should fail");
+    assert_eq!(ErrorKind::InvalidProof, error.kind());
+
+    // another enote
+    let (other_enote, other_encrypted_payment_id, _) = send_enote(&destination, 1234);
+    let error = out_proof
+        .verify(
+            &other_enote,
+            Some(&other_encrypted_payment_id),
+            &destination,
+            MESSAGE,
+        )
+        .expect_err("verify should fail");
+    assert_eq!(ErrorKind::InvalidProof, error.kind());
+
+    // tampered proof
+    let mut bad_out_proof = out_proof.clone();
+    bad_out_proof.response += curve25519_dalek::Scalar::ONE;
+    let error = bad_out_proof
+        .verify(&enote, Some(&encrypted_payment_id), &destination, MESSAGE)
+        .expect_err("verify should fail");
+    assert_eq!(ErrorKind::InvalidProof, error.kind());
+    let mut bad_out_proof_bytes = out_proof.to_bytes();
+    bad_out_proof_bytes[0] ^= 1;
+    let bad_out_proof = CarrotOutProofV1::from_bytes(&bad_out_proof_bytes).expect("from_bytes");
+    assert!(
+        bad_out_proof
+            .verify(&enote, Some(&encrypted_payment_id), &destination, MESSAGE)
+            .is_err()
+    );
+    bad_out_proof_bytes = out_proof.to_bytes();
+    bad_out_proof_bytes[95] = 0xff;
+    assert_eq!(None, CarrotOutProofV1::from_bytes(&bad_out_proof_bytes));
+}
+
+fn check_in_proof<VI: carrot_crypto::device::ViewIncomingKeyDevice>(
+    destination: &CarrotDestinationV1,
+    k_view_dev: &VI,
+) {
+    let (enote, encrypted_payment_id, _) = send_enote(destination, 5678);
+    let in_proof = CarrotInProofV1::prove(&enote, destination, k_view_dev, MESSAGE).expect("prove");
+    let record = in_proof
+        .verify(&enote, Some(&encrypted_payment_id), destination, MESSAGE)
+        .expect("verify");
+    assert_eq!(5678, record.amount);
+    assert_eq!(destination.payment_id, record.payment_id);
+
+    // proofs are deterministic
+    assert_eq!(
+        in_proof,
+        CarrotInProofV1::prove(&enote, destination, k_view_dev, MESSAGE).expect("prove")
+    );
+    assert_eq!(
+        Some(in_proof.clone()),
+        CarrotInProofV1::from_bytes(&in_proof.to_bytes())
+    );
+}
+
+#[test]
+fn in_proof_roundtrip() {
+    let bob: CarrotAccount = gen_random();
+    let index = gen_subaddress_index();
+    check_in_proof(&bob.main_address(), &bob);
+    check_in_proof(
+        &bob.subaddress(index.major, index.minor)
+            .expect("subaddress"),
+        &bob,
+    );
+    check_in_proof(&bob.integrated_address(gen_non_null_payment_id()), &bob);
+
+    // the view-incoming key alone is enough
+    check_in_proof(&bob.main_address(), bob.view_incoming_key());
+
+    // legacy addresses share the K^j_v = k_v B structure
+    let legacy_bob = carrot_crypto::legacy::LegacyAccount::from_spend_key(gen_random());
+    check_in_proof(&legacy_bob.main_address(), &legacy_bob);
+    check_in_proof(
+        &legacy_bob.subaddress(index.major, index.minor),
+        &legacy_bob,
+    );
+}
+
+#[test]
+fn in_proof_rejects() {
+    let bob: CarrotAccount = gen_random();
+    let carol: CarrotAccount = gen_random();
+    let destination = bob.subaddress(2, 3).expect("subaddress");
+    let (enote, encrypted_payment_id, _) = send_enote(&destination, 5678);
+
+    // carol's view key doesn't make bob's view pubkey
+    let error = CarrotInProofV1::prove(&enote, &destination, &carol, MESSAGE)
+        .expect_err("prove should fail");
+    assert_eq!(ErrorKind::ViewKeyMismatch, error.kind());
+
+    // bob can prove the ECDH exchange with an enote he didn't receive, but it doesn't scan
+    let (other_enote, other_encrypted_payment_id, _) =
+        send_enote(&carol.subaddress(2, 3).expect("subaddress"), 5678);
+    let error = CarrotInProofV1::prove(&other_enote, &destination, &bob, MESSAGE)
+        .expect("prove")
+        .verify(
+            &other_enote,
+            Some(&other_encrypted_payment_id),
+            &destination,
+            MESSAGE,
+        )
+        .expect_err("verify should fail");
+    assert!(matches!(error.kind(), ErrorKind::ScanFailed(_)));
+
+    let in_proof = CarrotInProofV1::prove(&enote, &destination, &bob, MESSAGE).expect("prove");
+
+    // another message, destination or enote
+    let error = in_proof
+        .verify(
+            &enote,
+            Some(&encrypted_payment_id),
+            &destination,
+            b"audit 2",
+        )
+        .expect_err("verify should fail");
+    assert_eq!(ErrorKind::InvalidProof, error.kind());
+    let error = in_proof
+        .verify(
+            &enote,
+            Some(&encrypted_payment_id),
+            &bob.subaddress(2, 4).expect("subaddress"),
+            MESSAGE,
+        )
+        .expect_err("verify should fail");
+    assert_eq!(ErrorKind::InvalidProof, error.kind());
+    let error = in_proof
+        .verify(
+            &other_enote,
+            Some(&other_encrypted_payment_id),
+            &destination,
+            MESSAGE,
+        )
+        .expect_err("verify should fail");
+    assert_eq!(ErrorKind::InvalidProof, error.kind());
+
+    // a shared secret point which isn't k_v K_e
+    let mut bad_in_proof = in_proof.clone();
+    bad_in_proof.shared_secret_point = gen_random();
+    let error = bad_in_proof
+        .verify(&enote, Some(&encrypted_payment_id), &destination, MESSAGE)
+        .expect_err("verify should fail");
+    assert_eq!(ErrorKind::InvalidProof, error.kind());
+}
@@ -0,0 +1,179 @@
+#![cfg(feature = "alloc")]
+
+mod common;
+use crate::common::keys::make_subaddress_table;
+use crate::common::random::*;
+
+use carrot_crypto::scan_tx::*;
+use carrot_crypto::*;
+
+#[test]
+fn tx_scan_payment_and_special_change() {
+    let alice: CarrotAccount = gen_random();
+    let bob: CarrotAccount = gen_random();
+
+    let tx_first_key_image: KeyImage = gen_random();
+
+    // alice pays bob's subaddress...
+    let payment_proposal = payments::CarrotPaymentProposalV1 {
+        destination: bob.subaddress(1, 2).expect("subaddress"),
+        amount: gen_random(),
+        randomness: gen_random(),
+    };
+    let (payment_enote_proposal, encrypted_payment_id) = payment_proposal
+        .get_normal_output_proposal(tx_first_key_image.clone())
+        .expect("get_normal_output_proposal");
+
+    // ...and sends her change to herself, sharing D_e
+    let change_proposal = payments::CarrotPaymentProposalSelfSendV1 {
+        destination_address_spend_pubkey: alice.account_spend_pubkey().clone(),
+        amount: gen_random(),
+        enote_type: CarrotEnoteType::Change,
+        enote_ephemeral_pubkey: None,
+        internal_message: None,
+    };
+    let change_enote_proposal = change_proposal
+        .get_special_output_proposal(
+            &alice,
+            tx_first_key_image,
+            &Some(payment_enote_proposal.enote.enote_ephemeral_pubkey.clone()),
+        )
+        .expect("get_special_output_proposal");
+
+    let enotes = [
+        change_enote_proposal.enote.clone(),
+        payment_enote_proposal.enote.clone(),
+    ];
+
+    // bob only finds the payment, tagged with its subaddress index
+    let bob_scan_result = try_scan_carrot_tx_external_receiver(
+        &enotes,
+        Some(&encrypted_payment_id),
+        &mut make_subaddress_table(&bob),
+        &bob,
+    )
+    .expect("try_scan_carrot_tx_external_receiver");
+    assert!(bob_scan_result.janus_failures.is_empty());
+    assert_eq!(1, bob_scan_result.owned_enotes.len());
+    assert_eq!(1, bob_scan_result.owned_enotes[0].output_index);
+    let CarrotOwnedEnoteRecordV1::External(bob_record) = &bob_scan_result.owned_enotes[0].record
+    else {
+        panic!("bob's payment should be found by the external scan path");
+    };
+    assert_eq!(
+        SubaddressIndexExtended {
+            index: SubaddressIndex { major: 1, minor: 2 },
+            derive_type: AddressDeriveType::Carrot,
+        },
+        bob_record.subaddress_index
+    );
+    assert_eq!(payment_proposal.amount, bob_record.record.amount);
+    assert_eq!(CarrotEnoteType::Payment, bob_record.record.enote_type);
+
+    // alice only finds her special change, even with her view-balance secret
+    let alice_scan_result = try_scan_carrot_tx_receiver(
+        &enotes,
+        Some(&encrypted_payment_id),
+        &mut make_subaddress_table(&alice),
+        &alice,
+    )
+    .expect("try_scan_carrot_tx_receiver");
+    assert!(alice_scan_result.janus_failures.is_empty());
+    assert_eq!(1, alice_scan_result.owned_enotes.len());
+    assert_eq!(0, alice_scan_result.owned_enotes[0].output_index);
+    let CarrotOwnedEnoteRecordV1::External(alice_record) =
+        &alice_scan_result.owned_enotes[0].record
+    else {
+        panic!("alice's special change should be found by the external scan path");
+    };
+    assert_eq!(change_proposal.amount, alice_record.record.amount);
+    assert_eq!(CarrotEnoteType::Change, alice_record.record.enote_type);
+}
+
+#[test]
+fn tx_scan_internal_change() {
+    let alice: CarrotAccount = gen_random();
+    let bob: CarrotAccount = gen_random();
+
+    let tx_first_key_image: KeyImage = gen_random();
+
+    // bob pays alice...
+    let payment_proposal = payments::CarrotPaymentProposalV1 {
+        destination: alice.main_address(),
+        amount: gen_random(),
+        randomness: gen_random(),
+    };
+    let (payment_enote_proposal, encrypted_payment_id) = payment_proposal
+        .get_normal_output_proposal(tx_first_key_image.clone())
+        .expect("get_normal_output_proposal");
+
+    // ...and sends his change to his own subaddress, internally
+    let change_proposal = payments::CarrotPaymentProposalSelfSendV1 {
+        destination_address_spend_pubkey: bob
+            .subaddress(0, 3)
+            .expect("subaddress")
+            .address_spend_pubkey,
+        amount: gen_random(),
+        enote_type: CarrotEnoteType::Change,
+        enote_ephemeral_pubkey: None,
+        internal_message: None,
+    };
+    let change_enote_proposal = change_proposal
+        .get_internal_output_proposal(
+            &bob,
+            tx_first_key_image,
+            &Some(payment_enote_proposal.enote.enote_ephemeral_pubkey.clone()),
+        )
+        .expect("get_internal_output_proposal");
+
+    let enotes = [
+        payment_enote_proposal.enote.clone(),
+        change_enote_proposal.enote.clone(),
+    ];
+
+    // bob's view-incoming key alone can't see internal change...
+    let view_incoming_scan_result = try_scan_carrot_tx_external_receiver(
+        &enotes,
+        Some(&encrypted_payment_id),
+        &mut make_subaddress_table(&bob),
+        &bob,
+    )
+    .expect("try_scan_carrot_tx_external_receiver");
+    assert_eq!(CarrotTxScanResultV1::default(), view_incoming_scan_result);
+
+    // ...but his view-balance secret can
+    let view_balance_scan_result = try_scan_carrot_tx_receiver(
+        &enotes,
+        Some(&encrypted_payment_id),
+        &mut make_subaddress_table(&bob),
+        &bob,
+    )
+    .expect("try_scan_carrot_tx_receiver");
+    assert!(view_balance_scan_result.janus_failures.is_empty());
+    assert_eq!(1, view_balance_scan_result.owned_enotes.len());
+    assert_eq!(1, view_balance_scan_result.owned_enotes[0].output_index);
+    let CarrotOwnedEnoteRecordV1::Internal(bob_record) =
+        &view_balance_scan_result.owned_enotes[0].record
+    else {
+        panic!("bob's internal change should be found by the internal scan path");
+    };
+    assert_eq!(
+        SubaddressIndexExtended {
+            index: SubaddressIndex { major: 0, minor: 3 },
+            derive_type: AddressDeriveType::Carrot,
+        },
+        bob_record.subaddress_index
+    );
+    assert_eq!(change_proposal.amount, bob_record.record.amount);
+
+    // alice finds her payment
+    let alice_scan_result = try_scan_carrot_tx_external_receiver(
+        &enotes,
+        Some(&encrypted_payment_id),
+        &mut make_subaddress_table(&alice),
+        &alice,
+    )
+    .expect("try_scan_carrot_tx_external_receiver");
+    assert_eq!(1, alice_scan_result.owned_enotes.len());
+    assert_eq!(0, alice_scan_result.owned_enotes[0].output_index);
+}